@@ -0,0 +1,82 @@
+//! Blame on a large generated file
+//!
+//! Builds a repository holding a 20,000-line file written over 200 commits, each rewriting
+//! a different stripe of lines, then blames the whole file.
+
+use std::path::Path;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use git2::{Repository, Signature};
+use git_time_machine::utils::{
+    commit_cache,
+    git_helpers::{self, BlameQuery},
+};
+
+const FILE_NAME: &str = "generated.rs";
+const LINE_COUNT: usize = 20_000;
+const COMMIT_COUNT: usize = 200;
+
+/// Commit `LINE_COUNT` lines, then rewrite one stripe of them per commit
+fn build_repository(dir: &Path) -> Repository {
+    let repo = Repository::init(dir).expect("init repository");
+    let stripe = LINE_COUNT / COMMIT_COUNT;
+    let mut lines: Vec<String> = (0..LINE_COUNT)
+        .map(|i| format!("pub const VALUE_{}: usize = {};", i, i))
+        .collect();
+
+    for n in 0..COMMIT_COUNT {
+        if n > 0 {
+            for line in &mut lines[n * stripe..(n + 1) * stripe] {
+                line.push_str(" // revised");
+            }
+        }
+        std::fs::write(dir.join(FILE_NAME), lines.join("\n") + "\n").expect("write file");
+
+        let mut index = repo.index().expect("open index");
+        index.add_path(Path::new(FILE_NAME)).expect("stage file");
+        index.write().expect("write index");
+        let tree = repo
+            .find_tree(index.write_tree().expect("write tree"))
+            .expect("find tree");
+
+        let time = git2::Time::new(1_700_000_000 + n as i64 * 3_600, 0);
+        let signature =
+            Signature::new("Bench", "bench@example.com", &time).expect("create signature");
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            &format!("Revise stripe {}\n\nGenerated for the blame benchmark.", n),
+            &tree,
+            &parent.iter().collect::<Vec<_>>(),
+        )
+        .expect("commit");
+    }
+
+    repo
+}
+
+fn blame_large_file(c: &mut Criterion) {
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let repo = build_repository(dir.path());
+    let query = BlameQuery::default();
+
+    let mut group = c.benchmark_group("blame_20k_lines");
+    group.sample_size(10);
+
+    commit_cache::set_shared(false);
+    group.bench_function("per_request_cache", |b| {
+        b.iter(|| git_helpers::get_file_blame(&repo, FILE_NAME, &query).expect("blame"))
+    });
+
+    commit_cache::set_shared(true);
+    group.bench_function("shared_cache", |b| {
+        b.iter(|| git_helpers::get_file_blame(&repo, FILE_NAME, &query).expect("blame"))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, blame_large_file);
+criterion_main!(benches);
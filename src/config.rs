@@ -0,0 +1,83 @@
+use std::path::PathBuf;
+
+use anyhow::bail;
+
+/// Command-line configuration
+#[derive(Debug, Default)]
+pub struct Config {
+    /// Serve MCP over stdin/stdout instead of HTTP
+    pub stdio: bool,
+    /// Repositories to serve as `(name, path)`; the first one is the default
+    pub repos: Vec<(Option<String>, PathBuf)>,
+    /// Directories scanned for repositories at startup
+    pub roots: Vec<PathBuf>,
+    /// Don't keep looked-up commits between requests
+    pub no_commit_cache: bool,
+    /// Browser origins allowed on `/mcp` besides localhost
+    pub allowed_origins: Vec<String>,
+}
+
+impl Config {
+    /// Parse the process arguments
+    ///
+    /// Supported flags:
+    /// - `--stdio`
+    /// - `--repo [NAME=]PATH`, repeatable
+    /// - `--root PATH`, repeatable
+    /// - `--no-commit-cache`
+    /// - `--allow-origin ORIGIN`, repeatable
+    pub fn from_args() -> anyhow::Result<Self> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    fn parse(args: impl Iterator<Item = String>) -> anyhow::Result<Self> {
+        let mut config = Config::default();
+        let mut args = args;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--stdio" => config.stdio = true,
+                "--no-commit-cache" => config.no_commit_cache = true,
+                "--repo" => {
+                    let Some(value) = args.next() else {
+                        bail!("--repo requires a value");
+                    };
+                    config.repos.push(parse_repo(&value));
+                }
+                "--root" => {
+                    let Some(value) = args.next() else {
+                        bail!("--root requires a value");
+                    };
+                    config.roots.push(PathBuf::from(value));
+                }
+                "--allow-origin" => {
+                    let Some(value) = args.next() else {
+                        bail!("--allow-origin requires a value");
+                    };
+                    config.allowed_origins.push(value);
+                }
+                other => {
+                    if let Some(value) = other.strip_prefix("--repo=") {
+                        config.repos.push(parse_repo(value));
+                    } else if let Some(value) = other.strip_prefix("--root=") {
+                        config.roots.push(PathBuf::from(value));
+                    } else if let Some(value) = other.strip_prefix("--allow-origin=") {
+                        config.allowed_origins.push(value.to_string());
+                    } else {
+                        bail!("Unknown argument: {}", other);
+                    }
+                }
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// Parse `NAME=PATH` or a bare `PATH`
+fn parse_repo(value: &str) -> (Option<String>, PathBuf) {
+    match value.split_once('=') {
+        Some((name, path)) if !name.is_empty() => (Some(name.to_string()), PathBuf::from(path)),
+        _ => (None, PathBuf::from(value)),
+    }
+}
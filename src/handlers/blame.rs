@@ -1,15 +1,139 @@
 use axum::{
     extract::Json,
-    http::StatusCode,
     response::{IntoResponse, Response},
 };
+use std::collections::BTreeMap;
 use tracing::{error, info};
 
 use crate::{
-    models::tool_schema::{BlameRequest, BlameResponse, BlameLine, CommitInfo},
-    utils::git_helpers,
+    models::tool_schema::{
+        BlameFormat, BlameHunk, BlameLine, BlameRequest, BlameResponse, CommitInfo,
+        ReverseBlameLine, ReverseBlameRequest, ReverseBlameResponse,
+    },
+    utils::{
+        git_helpers::{self, BlameLineInfo, BlameQuery, GitError},
+        repo_registry,
+    },
 };
 
+/// Build the blame response for a request
+pub fn blame_response(request: BlameRequest) -> Result<BlameResponse, GitError> {
+    let repo = repo_registry::open(request.repo.as_deref())?;
+    let query = BlameQuery {
+        rev: git_helpers::revision_as_of(&repo, request.rev.as_deref(), request.as_of.as_deref())?,
+        start_line: request.start_line,
+        end_line: request.end_line,
+        ignore_whitespace: request.ignore_whitespace,
+        track_copies_same_file: request.track_copies_same_file,
+        track_copies_same_commit_moves: request.track_copies_same_commit_moves,
+        track_copies_any_commit_copies: request.track_copies_any_commit_copies,
+        ignore_revs: request.ignore_revs,
+        no_ignore_revs_file: request.no_ignore_revs_file,
+    };
+    let blame_data = git_helpers::get_file_blame(&repo, &request.file, &query)?;
+
+    let mut response = BlameResponse {
+        file: request.file,
+        sha: blame_data.commit_sha,
+        lines: None,
+        hunks: None,
+        commits: None,
+    };
+
+    match request.format {
+        BlameFormat::Lines => {
+            response.lines = Some(blame_data.lines.into_iter().map(Into::into).collect());
+        }
+        BlameFormat::Hunks => {
+            let (hunks, commits) = group_hunks(blame_data.lines);
+            response.hunks = Some(hunks);
+            response.commits = Some(commits);
+        }
+    }
+
+    Ok(response)
+}
+
+/// Build the reverse blame response for a request
+pub fn reverse_blame_response(
+    request: ReverseBlameRequest,
+) -> Result<ReverseBlameResponse, GitError> {
+    let repo = repo_registry::open(request.repo.as_deref())?;
+    let rev = git_helpers::revision_as_of(&repo, Some(&request.rev), request.as_of.as_deref())?
+        .unwrap_or(request.rev);
+    let head = git_helpers::revision_as_of(
+        &repo,
+        request.head.as_deref(),
+        request.head_as_of.as_deref(),
+    )?;
+    let blame_data = git_helpers::get_file_reverse_blame(
+        &repo,
+        &request.file,
+        &rev,
+        head.as_deref(),
+        request.start_line,
+        request.end_line,
+    )?;
+
+    Ok(ReverseBlameResponse {
+        file: request.file,
+        sha: blame_data.commit_sha,
+        head_sha: blame_data.head_sha,
+        lines: blame_data
+            .lines
+            .into_iter()
+            .map(|line| ReverseBlameLine {
+                line_number: line.line_number,
+                content: line.content,
+                last_seen: line.last_seen_sha,
+                removed_in: line.removed_in_sha,
+            })
+            .collect(),
+        commits: blame_data
+            .commits
+            .into_iter()
+            .map(|commit| (commit.sha.clone(), commit.into()))
+            .collect(),
+    })
+}
+
+/// Collapse per-line blame into contiguous hunks and a table of the commits they reference
+fn group_hunks(lines: Vec<BlameLineInfo>) -> (Vec<BlameHunk>, BTreeMap<String, CommitInfo>) {
+    let mut hunks: Vec<BlameHunk> = Vec::new();
+    let mut commits = BTreeMap::new();
+
+    for line in lines {
+        let BlameLine {
+            line_number,
+            content,
+            commit,
+            reattributed_from,
+        } = line.into();
+
+        match hunks.last_mut() {
+            Some(hunk)
+                if hunk.sha == commit.sha
+                    && hunk.reattributed_from == reattributed_from
+                    && hunk.start_line + hunk.line_count == line_number =>
+            {
+                hunk.line_count += 1;
+                hunk.lines.push(content);
+            }
+            _ => hunks.push(BlameHunk {
+                start_line: line_number,
+                line_count: 1,
+                sha: commit.sha.clone(),
+                reattributed_from,
+                lines: vec![content],
+            }),
+        }
+
+        commits.entry(commit.sha.clone()).or_insert(commit);
+    }
+
+    (hunks, commits)
+}
+
 /// Get git blame information for a file
 ///
 /// Returns line-by-line blame metadata showing who last modified each line
@@ -20,43 +144,115 @@ use crate::{
     responses(
         (status = 200, description = "Blame information retrieved successfully", body = BlameResponse),
         (status = 400, description = "Invalid request"),
+        (status = 404, description = "File not found at the revision"),
         (status = 500, description = "Internal server error")
     )
 )]
 pub async fn get_git_blame(Json(request): Json<BlameRequest>) -> Response {
     info!("Processing get_git_blame request for file: {}", request.file);
     
-    match git_helpers::get_file_blame(&request.file) {
-        Ok(blame_data) => {
-            let blame_lines = blame_data
-                .into_iter()
-                .map(|line| BlameLine {
-                    line_number: line.line_number,
-                    content: line.content,
-                    commit: CommitInfo {
-                        sha: line.commit_sha,
-                        message: line.commit_message,
-                        author: line.author,
-                        email: line.email,
-                        timestamp: line.timestamp,
-                    },
-                })
-                .collect();
-
-            let response = BlameResponse {
-                file: request.file,
-                lines: blame_lines,
-            };
-
-            Json(response).into_response()
-        }
+    match blame_response(request) {
+        Ok(response) => Json(response).into_response(),
         Err(e) => {
             error!("Error getting git blame: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Error getting git blame: {}", e),
-            )
-                .into_response()
+            e.into_response()
         }
     }
 }
+
+/// Get reverse blame information for a file
+///
+/// Follows each line of an old revision forward, returning the last commit that contained it and the commit that changed or removed it
+#[utoipa::path(
+    post,
+    path = "/tools/get_reverse_blame",
+    request_body = ReverseBlameRequest,
+    responses(
+        (status = 200, description = "Reverse blame information retrieved successfully", body = ReverseBlameResponse),
+        (status = 400, description = "Invalid request"),
+        (status = 404, description = "File not found at the revision"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_reverse_blame(Json(request): Json<ReverseBlameRequest>) -> Response {
+    info!(
+        "Processing get_reverse_blame request for file: {} from: {}",
+        request.file, request.rev
+    );
+
+    match reverse_blame_response(request) {
+        Ok(response) => Json(response).into_response(),
+        Err(e) => {
+            error!("Error getting reverse blame: {}", e);
+            e.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(line_number: usize, sha: &str, reattributed_from: Option<&str>) -> BlameLineInfo {
+        BlameLineInfo {
+            line_number,
+            content: format!("line {}", line_number),
+            commit_sha: sha.to_string(),
+            commit_message: format!("Commit {}", sha),
+            author: "Test".to_string(),
+            email: "test@example.com".to_string(),
+            timestamp: "2024-01-01T00:00:00+00:00".to_string(),
+            reattributed_from: reattributed_from.map(str::to_string),
+            orig_path: "f".to_string(),
+        }
+    }
+
+    #[test]
+    fn hunks_split_on_commit_reattribution_and_gaps() {
+        let (hunks, commits) = group_hunks(vec![
+            line(1, "a", None),
+            line(2, "a", None),
+            line(3, "b", None),
+            line(4, "a", None),
+            line(5, "a", Some("fmt")),
+            line(6, "a", Some("fmt")),
+            // Line 7 was not blamed, so line 8 starts a new hunk
+            line(8, "a", Some("fmt")),
+        ]);
+
+        let summary: Vec<(usize, usize, &str, Option<&str>)> = hunks
+            .iter()
+            .map(|hunk| {
+                (
+                    hunk.start_line,
+                    hunk.line_count,
+                    hunk.sha.as_str(),
+                    hunk.reattributed_from.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, 2, "a", None),
+                (3, 1, "b", None),
+                (4, 1, "a", None),
+                (5, 2, "a", Some("fmt")),
+                (8, 1, "a", Some("fmt")),
+            ]
+        );
+        assert_eq!(hunks[0].lines, vec!["line 1", "line 2"]);
+        assert_eq!(hunks[3].lines, vec!["line 5", "line 6"]);
+
+        // Each commit is described once, however many hunks refer to it
+        assert_eq!(commits.keys().collect::<Vec<_>>(), vec!["a", "b"]);
+        assert_eq!(commits["b"].message, "Commit b");
+    }
+
+    #[test]
+    fn no_lines_make_no_hunks() {
+        let (hunks, commits) = group_hunks(Vec::new());
+        assert!(hunks.is_empty());
+        assert!(commits.is_empty());
+    }
+}
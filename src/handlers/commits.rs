@@ -1,18 +1,162 @@
 use axum::{
     extract::Json,
-    http::StatusCode,
     response::{IntoResponse, Response},
 };
 use tracing::{error, info};
 
 use crate::{
     models::tool_schema::{
-        CommitInfo, CommitsAffectingRequest, CommitsAffectingResponse, FileAtCommitRequest,
-        FileAtCommitResponse,
+        CommitsAffectingRequest, CommitsAffectingResponse, FileAtCommitRequest,
+        FileAtCommitResponse, LineHistoryRequest, LineHistoryResponse, PickaxeRequest,
+        PickaxeResponse, SearchCommitsRequest, SearchCommitsResponse, SymbolHistoryRequest,
+        SymbolHistoryResponse,
+    },
+    utils::{
+        git_helpers::{
+            self, CommitSearchQuery, GitError, HistoryQuery, LineHistoryQuery, PickaxeQuery,
+            SymbolHistoryQuery,
+        },
+        repo_registry,
     },
-    utils::git_helpers,
 };
 
+/// Build the list of commits affecting a file for a request
+pub fn commits_affecting_response(
+    request: CommitsAffectingRequest,
+) -> Result<CommitsAffectingResponse, GitError> {
+    let repo = repo_registry::open(request.repo.as_deref())?;
+    let query = HistoryQuery {
+        start_rev: git_helpers::revision_as_of(
+            &repo,
+            request.start_rev.as_deref(),
+            request.as_of.as_deref(),
+        )?,
+        cursor: request.cursor,
+        limit: request.limit,
+        follow: request.follow,
+        first_parent: request.first_parent,
+        full_history: request.full_history,
+    };
+    let history = git_helpers::get_commits_affecting_file(&repo, &request.file, &query)?;
+
+    Ok(CommitsAffectingResponse {
+        file: request.file,
+        start_sha: history.start_sha,
+        commits: history.commits.into_iter().map(Into::into).collect(),
+        cursor: history.cursor,
+    })
+}
+
+/// Build the history of a line range for a request
+pub fn line_history_response(
+    request: LineHistoryRequest,
+) -> Result<LineHistoryResponse, GitError> {
+    let repo = repo_registry::open(request.repo.as_deref())?;
+    let query = LineHistoryQuery {
+        start_rev: git_helpers::revision_as_of(
+            &repo,
+            request.start_rev.as_deref(),
+            request.as_of.as_deref(),
+        )?,
+        start_line: request.start_line,
+        end_line: request.end_line,
+        function: request.function,
+        limit: request.limit,
+    };
+    let history = git_helpers::get_line_history(&repo, &request.file, &query)?;
+
+    Ok(LineHistoryResponse {
+        file: request.file,
+        start_sha: history.start_sha,
+        commits: history.entries.into_iter().map(Into::into).collect(),
+    })
+}
+
+/// Build the history of a named symbol for a request
+pub fn symbol_history_response(
+    request: SymbolHistoryRequest,
+) -> Result<SymbolHistoryResponse, GitError> {
+    let repo = repo_registry::open(request.repo.as_deref())?;
+    let query = SymbolHistoryQuery {
+        start_rev: git_helpers::revision_as_of(
+            &repo,
+            request.start_rev.as_deref(),
+            request.as_of.as_deref(),
+        )?,
+        limit: request.limit,
+    };
+    let history = git_helpers::get_symbol_history(&repo, &request.file, &request.symbol, &query)?;
+
+    Ok(SymbolHistoryResponse {
+        file: request.file,
+        symbol: request.symbol,
+        start_sha: history.start_sha,
+        kind: history.kind,
+        commits: history.entries.into_iter().map(Into::into).collect(),
+    })
+}
+
+/// Build the commit search results for a request
+pub fn search_commits_response(
+    request: SearchCommitsRequest,
+) -> Result<SearchCommitsResponse, GitError> {
+    let repo = repo_registry::open(request.repo.as_deref())?;
+    let query = CommitSearchQuery {
+        range: git_helpers::range_as_of(&repo, request.range.as_deref(), request.as_of.as_deref())?,
+        author: request.author,
+        committer: request.committer,
+        since: request.since,
+        until: request.until,
+        message: request.message,
+        paths: request.paths,
+        merges: request.merges,
+        limit: request.limit,
+        cursor: request.cursor,
+    };
+    let result = git_helpers::search_commits(&repo, &query)?;
+
+    Ok(SearchCommitsResponse {
+        commits: result.commits.into_iter().map(Into::into).collect(),
+        cursor: result.cursor,
+    })
+}
+
+/// Build the pickaxe search results for a request
+pub fn pickaxe_response(request: PickaxeRequest) -> Result<PickaxeResponse, GitError> {
+    let repo = repo_registry::open(request.repo.as_deref())?;
+    let query = PickaxeQuery {
+        pattern: request.pattern,
+        regex: request.regex,
+        mode: request.mode,
+        range: git_helpers::range_as_of(&repo, request.range.as_deref(), request.as_of.as_deref())?,
+        paths: request.paths,
+        limit: request.limit,
+        cursor: request.cursor,
+    };
+    let result = git_helpers::pickaxe_search(&repo, &query)?;
+
+    Ok(PickaxeResponse {
+        commits: result.commits.into_iter().map(Into::into).collect(),
+        cursor: result.cursor,
+    })
+}
+
+/// Build the file-at-commit response for a request
+pub fn file_at_commit_response(
+    request: FileAtCommitRequest,
+) -> Result<FileAtCommitResponse, GitError> {
+    let repo = repo_registry::open(request.repo.as_deref())?;
+    let sha = git_helpers::revision_as_of(&repo, request.sha.as_deref(), request.as_of.as_deref())?
+        .unwrap_or_else(|| "HEAD".to_string());
+    let file_data = git_helpers::get_file_at_commit(&repo, &request.file, &sha)?;
+
+    Ok(FileAtCommitResponse {
+        file: request.file,
+        commit: file_data.commit.into(),
+        content: file_data.content,
+    })
+}
+
 /// Get a list of commits that modified a file
 ///
 /// Returns metadata for commits that affected the given file
@@ -32,33 +176,125 @@ pub async fn get_commits_affecting(Json(request): Json<CommitsAffectingRequest>)
         request.file
     );
     
-    match git_helpers::get_commits_affecting_file(&request.file, request.limit) {
-        Ok(commits_data) => {
-            let commits = commits_data
-                .into_iter()
-                .map(|commit| CommitInfo {
-                    sha: commit.sha,
-                    message: commit.message,
-                    author: commit.author,
-                    email: commit.email,
-                    timestamp: commit.timestamp,
-                })
-                .collect();
-
-            let response = CommitsAffectingResponse {
-                file: request.file,
-                commits,
-            };
-
-            Json(response).into_response()
-        }
+    match commits_affecting_response(request) {
+        Ok(response) => Json(response).into_response(),
         Err(e) => {
             error!("Error getting commits: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Error getting commits: {}", e),
-            )
-                .into_response()
+            e.into_response()
+        }
+    }
+}
+
+/// Trace the history of a range of lines
+///
+/// Returns the commits that changed the given lines or function, each with the range's diff
+#[utoipa::path(
+    post,
+    path = "/tools/get_line_history",
+    request_body = LineHistoryRequest,
+    responses(
+        (status = 200, description = "Line history retrieved successfully", body = LineHistoryResponse),
+        (status = 400, description = "Invalid request"),
+        (status = 404, description = "File not found at the revision"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_line_history(Json(request): Json<LineHistoryRequest>) -> Response {
+    info!(
+        "Processing get_line_history request for file: {}",
+        request.file
+    );
+    
+    match line_history_response(request) {
+        Ok(response) => Json(response).into_response(),
+        Err(e) => {
+            error!("Error getting line history: {}", e);
+            e.into_response()
+        }
+    }
+}
+
+/// Trace the history of a function, class or other named definition
+///
+/// Returns the commits that changed the definition's body, located with a syntax parser in each version
+#[utoipa::path(
+    post,
+    path = "/tools/get_symbol_history",
+    request_body = SymbolHistoryRequest,
+    responses(
+        (status = 200, description = "Symbol history retrieved successfully", body = SymbolHistoryResponse),
+        (status = 400, description = "Invalid request"),
+        (status = 404, description = "File not found at the revision"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_symbol_history(Json(request): Json<SymbolHistoryRequest>) -> Response {
+    info!(
+        "Processing get_symbol_history request for {} in file: {}",
+        request.symbol, request.file
+    );
+    
+    match symbol_history_response(request) {
+        Ok(response) => Json(response).into_response(),
+        Err(e) => {
+            error!("Error getting symbol history: {}", e);
+            e.into_response()
+        }
+    }
+}
+
+/// Search commits by author, committer, date, message and path
+///
+/// Returns a page of matching commits from a revision or range, newest first
+#[utoipa::path(
+    post,
+    path = "/tools/search_commits",
+    request_body = SearchCommitsRequest,
+    responses(
+        (status = 200, description = "Commits searched successfully", body = SearchCommitsResponse),
+        (status = 400, description = "Invalid request"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn search_commits(Json(request): Json<SearchCommitsRequest>) -> Response {
+    info!(
+        "Processing search_commits request for range: {}",
+        request.range.as_deref().unwrap_or("HEAD")
+    );
+    
+    match search_commits_response(request) {
+        Ok(response) => Json(response).into_response(),
+        Err(e) => {
+            error!("Error searching commits: {}", e);
+            e.into_response()
+        }
+    }
+}
+
+/// Find commits that added or removed a string
+///
+/// Returns commits whose changes altered the occurrences of a string or regex, with the matching hunks
+#[utoipa::path(
+    post,
+    path = "/tools/pickaxe_search",
+    request_body = PickaxeRequest,
+    responses(
+        (status = 200, description = "Pickaxe search completed successfully", body = PickaxeResponse),
+        (status = 400, description = "Invalid request"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn pickaxe_search(Json(request): Json<PickaxeRequest>) -> Response {
+    info!(
+        "Processing pickaxe_search request for pattern: {}",
+        request.pattern
+    );
+    
+    match pickaxe_response(request) {
+        Ok(response) => Json(response).into_response(),
+        Err(e) => {
+            error!("Error running pickaxe search: {}", e);
+            e.into_response()
         }
     }
 }
@@ -73,38 +309,22 @@ pub async fn get_commits_affecting(Json(request): Json<CommitsAffectingRequest>)
     responses(
         (status = 200, description = "File retrieved successfully", body = FileAtCommitResponse),
         (status = 400, description = "Invalid request"),
+        (status = 404, description = "File not found at the revision"),
         (status = 500, description = "Internal server error")
     )
 )]
 pub async fn get_file_at_commit(Json(request): Json<FileAtCommitRequest>) -> Response {
     info!(
         "Processing get_file_at_commit request for file: {} at commit: {}",
-        request.file, request.sha
+        request.file,
+        request.sha.as_deref().unwrap_or("HEAD")
     );
     
-    match git_helpers::get_file_at_commit(&request.file, &request.sha) {
-        Ok(file_data) => {
-            let response = FileAtCommitResponse {
-                file: request.file,
-                commit: CommitInfo {
-                    sha: file_data.commit.sha,
-                    message: file_data.commit.message,
-                    author: file_data.commit.author,
-                    email: file_data.commit.email,
-                    timestamp: file_data.commit.timestamp,
-                },
-                content: file_data.content,
-            };
-
-            Json(response).into_response()
-        }
+    match file_at_commit_response(request) {
+        Ok(response) => Json(response).into_response(),
         Err(e) => {
             error!("Error getting file: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Error getting file: {}", e),
-            )
-                .into_response()
+            e.into_response()
         }
     }
 }
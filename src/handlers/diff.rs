@@ -1,18 +1,77 @@
 use axum::{
     extract::Json,
-    http::StatusCode,
     response::{IntoResponse, Response},
 };
 use tracing::{error, info};
 
 use crate::{
     models::tool_schema::{
-        CommitDiffRequest, CommitDiffResponse, CommitInfo, FileChange,
-        SummarizeDiffRequest, SummarizeDiffResponse,
+        CommitDiffRequest, CommitDiffResponse, CommitInfo, SummarizeDiffRequest,
+        SummarizeDiffResponse,
+    },
+    utils::{
+        git_helpers::{self, GitError},
+        repo_registry,
     },
-    utils::git_helpers,
 };
 
+/// Build the commit diff response for a request
+pub fn commit_diff_response(request: CommitDiffRequest) -> Result<CommitDiffResponse, GitError> {
+    let repo = repo_registry::open(request.repo.as_deref())?;
+    let sha = git_helpers::revision_as_of(&repo, request.sha.as_deref(), request.as_of.as_deref())?
+        .unwrap_or_else(|| "HEAD".to_string());
+    let diff_data = git_helpers::get_commit_diff(&repo, &sha)?;
+
+    Ok(CommitDiffResponse {
+        commit: CommitInfo {
+            sha: diff_data.commit_sha,
+            message: diff_data.commit_message,
+            author: diff_data.author,
+            email: diff_data.email,
+            timestamp: diff_data.timestamp,
+        },
+        changes: diff_data.changes.into_iter().map(Into::into).collect(),
+    })
+}
+
+/// Build the diff summary response for a request
+pub fn summarize_diff_response(
+    request: SummarizeDiffRequest,
+) -> Result<SummarizeDiffResponse, GitError> {
+    let repo = repo_registry::open(request.repo.as_deref())?;
+    let base_as_of = request.base_as_of.as_deref();
+    let head_as_of = request.head_as_of.as_deref();
+    let summary_data = match (&request.range, &request.base, &request.head) {
+        (Some(range), None, None) => {
+            git_helpers::summarize_range(&repo, range, base_as_of, head_as_of)?
+        }
+        (Some(_), _, _) => {
+            return Err(GitError::InvalidArgument(
+                "`range` cannot be combined with `base` or `head`".to_string(),
+            ))
+        }
+        (None, base, head) => {
+            let base = git_helpers::revision_as_of(&repo, base.as_deref(), base_as_of)?;
+            let head = git_helpers::revision_as_of(&repo, head.as_deref(), head_as_of)?;
+            match (base, head) {
+                (Some(base), Some(head)) => git_helpers::summarize_diff(&repo, &base, &head)?,
+                _ => {
+                    return Err(GitError::InvalidArgument(
+                        "expected either `range` or both `base` and `head`".to_string(),
+                    ))
+                }
+            }
+        }
+    };
+
+    Ok(SummarizeDiffResponse {
+        base_commit: summary_data.base_commit.into(),
+        head_commit: summary_data.head_commit.into(),
+        summary: summary_data.summary,
+        changes: summary_data.changes.into_iter().map(Into::into).collect(),
+    })
+}
+
 /// Get the full diff for a specific commit
 ///
 /// Returns the complete diff and metadata for a specific commit
@@ -27,40 +86,16 @@ use crate::{
     )
 )]
 pub async fn get_commit_diff(Json(request): Json<CommitDiffRequest>) -> Response {
-    info!("Processing get_commit_diff request for SHA: {}", request.sha);
+    info!(
+        "Processing get_commit_diff request for SHA: {}",
+        request.sha.as_deref().unwrap_or("HEAD")
+    );
     
-    match git_helpers::get_commit_diff(&request.sha) {
-        Ok(diff_data) => {
-            let response = CommitDiffResponse {
-                commit: CommitInfo {
-                    sha: diff_data.commit_sha,
-                    message: diff_data.commit_message,
-                    author: diff_data.author,
-                    email: diff_data.email,
-                    timestamp: diff_data.timestamp,
-                },
-                changes: diff_data
-                    .changes
-                    .into_iter()
-                    .map(|change| FileChange {
-                        path: change.path,
-                        change_type: change.change_type,
-                        diff: change.diff,
-                        additions: change.additions,
-                        deletions: change.deletions,
-                    })
-                    .collect(),
-            };
-
-            Json(response).into_response()
-        }
+    match commit_diff_response(request) {
+        Ok(response) => Json(response).into_response(),
         Err(e) => {
             error!("Error getting commit diff: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Error getting commit diff: {}", e),
-            )
-                .into_response()
+            e.into_response()
         }
     }
 }
@@ -80,50 +115,15 @@ pub async fn get_commit_diff(Json(request): Json<CommitDiffRequest>) -> Response
 )]
 pub async fn summarize_diff(Json(request): Json<SummarizeDiffRequest>) -> Response {
     info!(
-        "Processing summarize_diff request for base: {} and head: {}",
-        request.base, request.head
+        "Processing summarize_diff request for base: {:?}, head: {:?}, range: {:?}",
+        request.base, request.head, request.range
     );
     
-    match git_helpers::summarize_diff(&request.base, &request.head) {
-        Ok(summary_data) => {
-            let response = SummarizeDiffResponse {
-                base_commit: CommitInfo {
-                    sha: summary_data.base_commit.sha,
-                    message: summary_data.base_commit.message,
-                    author: summary_data.base_commit.author,
-                    email: summary_data.base_commit.email,
-                    timestamp: summary_data.base_commit.timestamp,
-                },
-                head_commit: CommitInfo {
-                    sha: summary_data.head_commit.sha,
-                    message: summary_data.head_commit.message,
-                    author: summary_data.head_commit.author,
-                    email: summary_data.head_commit.email,
-                    timestamp: summary_data.head_commit.timestamp,
-                },
-                summary: summary_data.summary,
-                changes: summary_data
-                    .changes
-                    .into_iter()
-                    .map(|change| FileChange {
-                        path: change.path,
-                        change_type: change.change_type,
-                        diff: change.diff,
-                        additions: change.additions,
-                        deletions: change.deletions,
-                    })
-                    .collect(),
-            };
-
-            Json(response).into_response()
-        }
+    match summarize_diff_response(request) {
+        Ok(response) => Json(response).into_response(),
         Err(e) => {
             error!("Error summarizing diff: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Error summarizing diff: {}", e),
-            )
-                .into_response()
+            e.into_response()
         }
     }
 }
@@ -8,24 +8,53 @@ use utoipa::OpenApi;
 #[openapi(
     paths(
         crate::handlers::blame::get_git_blame,
+        crate::handlers::blame::get_reverse_blame,
         crate::handlers::diff::get_commit_diff,
         crate::handlers::diff::summarize_diff,
         crate::handlers::commits::get_commits_affecting,
+        crate::handlers::commits::get_line_history,
+        crate::handlers::commits::get_symbol_history,
+        crate::handlers::commits::search_commits,
+        crate::handlers::commits::pickaxe_search,
         crate::handlers::commits::get_file_at_commit,
+        crate::handlers::repositories::list_repositories,
     ),
     components(
         schemas(
             crate::models::tool_schema::BlameRequest,
             crate::models::tool_schema::BlameResponse,
             crate::models::tool_schema::BlameLine,
+            crate::models::tool_schema::BlameFormat,
+            crate::models::tool_schema::BlameHunk,
+            crate::models::tool_schema::ReverseBlameRequest,
+            crate::models::tool_schema::ReverseBlameResponse,
+            crate::models::tool_schema::ReverseBlameLine,
             crate::models::tool_schema::CommitDiffRequest, 
             crate::models::tool_schema::CommitDiffResponse,
             crate::models::tool_schema::SummarizeDiffRequest, 
             crate::models::tool_schema::SummarizeDiffResponse,
             crate::models::tool_schema::CommitsAffectingRequest,
             crate::models::tool_schema::CommitsAffectingResponse,
+            crate::models::tool_schema::FileCommit,
+            crate::models::tool_schema::LineHistoryRequest,
+            crate::models::tool_schema::LineHistoryResponse,
+            crate::models::tool_schema::LineHistoryCommit,
+            crate::models::tool_schema::SymbolHistoryRequest,
+            crate::models::tool_schema::SymbolHistoryResponse,
+            crate::models::tool_schema::SymbolHistoryCommit,
+            crate::models::tool_schema::SearchCommitsRequest,
+            crate::models::tool_schema::SearchCommitsResponse,
+            crate::models::tool_schema::MergeFilter,
+            crate::models::tool_schema::PickaxeRequest,
+            crate::models::tool_schema::PickaxeResponse,
+            crate::models::tool_schema::PickaxeMode,
+            crate::models::tool_schema::PickaxeCommit,
+            crate::models::tool_schema::PickaxeFile,
             crate::models::tool_schema::FileAtCommitRequest,
             crate::models::tool_schema::FileAtCommitResponse,
+            crate::models::tool_schema::ListRepositoriesRequest,
+            crate::models::tool_schema::ListRepositoriesResponse,
+            crate::models::tool_schema::RepositoryInfo,
             crate::models::tool_schema::CommitInfo,
             crate::models::tool_schema::FileChange,
             crate::models::tool_schema::ChangeType,
@@ -36,7 +65,7 @@ use utoipa::OpenApi;
         (name = "Git Time Machine", description = "Git history exploration tools")
     )
 )]
-struct ApiDoc;
+pub struct ApiDoc;
 
 /// Get the AI plugin manifest for integration with AI assistants
 pub async fn get_plugin_manifest() -> impl IntoResponse {
@@ -1,4 +1,54 @@
+//! REST endpoints for the tools
+//!
+//! Each tool's `*_response` function builds its response; the REST handlers here and the
+//! MCP tool dispatcher both call it.
+
 pub mod blame;
 pub mod commits;
 pub mod diff;
 pub mod metadata;
+pub mod repositories;
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+use crate::utils::git_helpers::GitError;
+
+/// Bad input is the client's fault, a missing file is a 404, anything else a server error
+impl IntoResponse for GitError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            GitError::InvalidArgument(_)
+            | GitError::InvalidCommit(_)
+            | GitError::UnknownRepository(_) => StatusCode::BAD_REQUEST,
+            GitError::FileNotFound(_) => StatusCode::NOT_FOUND,
+            GitError::GitError(_) | GitError::IoError(_) | GitError::Other(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        };
+
+        (status, self.to_string()).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn git_errors_map_to_status_codes() {
+        let status = |error: GitError| error.into_response().status();
+
+        assert_eq!(status(GitError::InvalidArgument("limit".into())), StatusCode::BAD_REQUEST);
+        assert_eq!(status(GitError::InvalidCommit("nope".into())), StatusCode::BAD_REQUEST);
+        assert_eq!(status(GitError::UnknownRepository("x".into())), StatusCode::BAD_REQUEST);
+        assert_eq!(status(GitError::FileNotFound("a.rs".into())), StatusCode::NOT_FOUND);
+        assert_eq!(status(GitError::Other("boom".into())), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            status(git2::Error::from_str("corrupt").into()),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+}
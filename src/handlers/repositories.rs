@@ -0,0 +1,74 @@
+use axum::{
+    extract::Json,
+    response::{IntoResponse, Response},
+};
+use tracing::{error, info, warn};
+
+use crate::{
+    models::tool_schema::{ListRepositoriesRequest, ListRepositoriesResponse, RepositoryInfo},
+    utils::{
+        git_helpers::{self, GitError},
+        repo_registry,
+    },
+};
+
+/// Build the list of repositories this server can query
+pub fn list_repositories_response(
+    _request: ListRepositoriesRequest,
+) -> Result<ListRepositoriesResponse, GitError> {
+    let registry = repo_registry::registry();
+    let default_name = registry.default_name();
+
+    let mut repositories = Vec::new();
+    for (name, path) in registry.iter() {
+        // A repository that vanished since startup should not hide the others
+        let details = match repo_registry::open(Some(name))
+            .and_then(|repo| git_helpers::get_repository_details(&repo))
+        {
+            Ok(details) => details,
+            Err(e) => {
+                warn!("Skipping repository '{}': {}", name, e);
+                continue;
+            }
+        };
+
+        repositories.push(RepositoryInfo {
+            name: name.to_string(),
+            path: path.display().to_string(),
+            is_default: default_name == Some(name),
+            bare: details.bare,
+            worktree: details.worktree,
+            head: details.head,
+            head_ref: details.head_ref,
+            default_branch: details.default_branch,
+            last_commit_time: details.last_commit_time,
+        });
+    }
+
+    Ok(ListRepositoriesResponse { repositories })
+}
+
+/// List the repositories this server can query
+///
+/// Returns the name to pass as `repo` in other requests, along with each repository's HEAD
+#[utoipa::path(
+    post,
+    path = "/tools/list_repositories",
+    request_body = ListRepositoriesRequest,
+    responses(
+        (status = 200, description = "Repositories listed successfully", body = ListRepositoriesResponse),
+        (status = 400, description = "Invalid request"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn list_repositories(Json(request): Json<ListRepositoriesRequest>) -> Response {
+    info!("Processing list_repositories request");
+
+    match list_repositories_response(request) {
+        Ok(response) => Json(response).into_response(),
+        Err(e) => {
+            error!("Error listing repositories: {}", e);
+            e.into_response()
+        }
+    }
+}
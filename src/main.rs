@@ -1,32 +1,65 @@
-mod handlers;
-mod models;
-mod utils;
-
 use axum::{
+    http::HeaderName,
     routing::{get, post},
     Router,
 };
 use std::net::SocketAddr;
+use std::sync::Arc;
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
-use tracing::info;
+use tracing::{info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use git_time_machine::{
+    config, handlers, mcp,
+    utils::{
+        commit_cache,
+        repo_registry::{self, RepoRegistry},
+    },
+};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Initialize logging
+    let config = config::Config::from_args()?;
+
+    // Initialize logging; logs go to stderr so stdout stays free for the stdio transport
     tracing_subscriber::registry()
         .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
-        .with(tracing_subscriber::fmt::layer())
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
         .init();
     
     info!("Starting Git Time Machine MCP server");
 
+    // Register the repositories to serve, defaulting to the current directory
+    let mut registry = RepoRegistry::new();
+    for (name, path) in &config.repos {
+        registry
+            .insert(name.clone(), path)
+            .map_err(|e| anyhow::anyhow!("Cannot serve repository {:?}: {}", path, e))?;
+    }
+    for root in &config.roots {
+        registry.discover(root);
+    }
+    if config.repos.is_empty() && config.roots.is_empty() {
+        if let Err(e) = registry.insert(None, &std::env::current_dir()?) {
+            warn!("Current directory is not a git repository: {}", e);
+        }
+    }
+    repo_registry::init(registry);
+    commit_cache::set_shared(!config.no_commit_cache);
+
+    let mcp_server = Arc::new(mcp::McpServer::new());
+    mcp::watcher::spawn(Arc::clone(&mcp_server));
+
+    if config.stdio {
+        return mcp::stdio::serve(mcp_server).await;
+    }
+
     // CORS middleware to allow requests from anywhere
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
-        .allow_headers(Any);
+        .allow_headers(Any)
+        .expose_headers([HeaderName::from_static(mcp::http::SESSION_ID_HEADER)]);
 
     // Build the application router
     let app = Router::new()
@@ -36,14 +69,23 @@ async fn main() -> anyhow::Result<()> {
         
         // Tool endpoints
         .route("/tools/get_git_blame", post(handlers::blame::get_git_blame))
+        .route("/tools/get_reverse_blame", post(handlers::blame::get_reverse_blame))
         .route("/tools/get_commit_diff", post(handlers::diff::get_commit_diff))
         .route("/tools/summarize_diff", post(handlers::diff::summarize_diff))
         .route("/tools/get_commits_affecting", post(handlers::commits::get_commits_affecting))
+        .route("/tools/get_line_history", post(handlers::commits::get_line_history))
+        .route("/tools/get_symbol_history", post(handlers::commits::get_symbol_history))
+        .route("/tools/search_commits", post(handlers::commits::search_commits))
+        .route("/tools/pickaxe_search", post(handlers::commits::pickaxe_search))
         .route("/tools/get_file_at_commit", post(handlers::commits::get_file_at_commit))
+        .route("/tools/list_repositories", post(handlers::repositories::list_repositories))
         
         // OpenAPI schema for tools
         .route("/openapi.json", get(handlers::metadata::get_openapi_schema))
         
+        // MCP streamable HTTP endpoint
+        .merge(mcp::http::router(mcp_server, config.allowed_origins))
+        
         // Add middleware
         .layer(cors)
         .layer(TraceLayer::new_for_http());
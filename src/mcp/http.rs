@@ -0,0 +1,411 @@
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::post,
+    Json, Router,
+};
+use serde_json::Value;
+use tokio::sync::{broadcast::error::RecvError, watch};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use super::McpServer;
+use crate::models::mcp::{error_codes, JsonRpcResponse, SUPPORTED_PROTOCOL_VERSIONS};
+
+/// Header carrying the session id assigned at initialization
+pub const SESSION_ID_HEADER: &str = "mcp-session-id";
+
+/// Header carrying the negotiated protocol version on follow-up requests
+pub const PROTOCOL_VERSION_HEADER: &str = "mcp-protocol-version";
+
+/// How long a session without requests or an open stream is kept
+const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Most sessions kept at once; the least recently used one is dropped to make room
+const MAX_SESSIONS: usize = 1024;
+
+/// Shared state for the streamable HTTP transport
+#[derive(Clone)]
+struct HttpState {
+    server: Arc<McpServer>,
+    sessions: Arc<Sessions>,
+    allowed_origins: Arc<Vec<String>>,
+}
+
+/// A live session
+struct Session {
+    last_seen: Instant,
+    /// Dropped when the session ends, which closes its notification streams
+    closed: watch::Sender<()>,
+}
+
+impl Session {
+    fn is_idle(&self, now: Instant, timeout: Duration) -> bool {
+        self.closed.receiver_count() == 0 && now.duration_since(self.last_seen) > timeout
+    }
+}
+
+/// The sessions created by `initialize`, dropped on `DELETE`, after idling or when over the cap
+struct Sessions {
+    sessions: Mutex<HashMap<String, Session>>,
+    idle_timeout: Duration,
+    max_sessions: usize,
+}
+
+impl Sessions {
+    fn new(idle_timeout: Duration, max_sessions: usize) -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+            idle_timeout,
+            max_sessions,
+        }
+    }
+
+    /// Start a new session, returning its id and the ids of the sessions dropped to make room
+    fn create(&self) -> (String, Vec<String>) {
+        let now = Instant::now();
+        let mut sessions = self.sessions.lock().unwrap();
+
+        let mut dropped: Vec<String> = sessions
+            .iter()
+            .filter(|(_, session)| session.is_idle(now, self.idle_timeout))
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in &dropped {
+            sessions.remove(id);
+        }
+        while sessions.len() >= self.max_sessions.max(1) {
+            let Some(oldest) = sessions
+                .iter()
+                .min_by_key(|(_, session)| session.last_seen)
+                .map(|(id, _)| id.clone())
+            else {
+                break;
+            };
+            sessions.remove(&oldest);
+            dropped.push(oldest);
+        }
+
+        let id = Uuid::new_v4().to_string();
+        let (closed, _) = watch::channel(());
+        sessions.insert(id.clone(), Session { last_seen: now, closed });
+        (id, dropped)
+    }
+
+    /// Mark a session as used, returning whether it is still live
+    fn touch(&self, id: &str) -> bool {
+        let now = Instant::now();
+        let mut sessions = self.sessions.lock().unwrap();
+        match sessions.get_mut(id) {
+            Some(session) if !session.is_idle(now, self.idle_timeout) => {
+                session.last_seen = now;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// A signal that resolves once the session ends, if it is still live
+    fn closed(&self, id: &str) -> Option<watch::Receiver<()>> {
+        if !self.touch(id) {
+            return None;
+        }
+        let sessions = self.sessions.lock().unwrap();
+        sessions.get(id).map(|session| session.closed.subscribe())
+    }
+
+    /// End a session, returning whether it existed
+    fn remove(&self, id: &str) -> bool {
+        self.sessions.lock().unwrap().remove(id).is_some()
+    }
+}
+
+/// Build the router serving the MCP streamable HTTP endpoint at `/mcp`
+///
+/// Browser requests are only accepted from localhost and `allowed_origins`.
+pub fn router(server: Arc<McpServer>, allowed_origins: Vec<String>) -> Router {
+    let state = HttpState {
+        server,
+        sessions: Arc::new(Sessions::new(SESSION_IDLE_TIMEOUT, MAX_SESSIONS)),
+        allowed_origins: Arc::new(allowed_origins),
+    };
+
+    Router::new()
+        .route(
+            "/mcp",
+            post(handle_post).get(handle_get).delete(handle_delete),
+        )
+        .with_state(state)
+}
+
+/// Handle a JSON-RPC message posted by the client
+async fn handle_post(State(state): State<HttpState>, headers: HeaderMap, body: Bytes) -> Response {
+    if !origin_allowed(&state, &headers) {
+        return (StatusCode::FORBIDDEN, "Origin not allowed").into_response();
+    }
+    if let Some(version) = headers.get(PROTOCOL_VERSION_HEADER) {
+        let supported = version
+            .to_str()
+            .map(|v| SUPPORTED_PROTOCOL_VERSIONS.contains(&v))
+            .unwrap_or(false);
+        if !supported {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!("Unsupported MCP protocol version: {:?}", version),
+            )
+                .into_response();
+        }
+    }
+
+    let message: Value = match serde_json::from_slice(&body) {
+        Ok(message) => message,
+        Err(e) => {
+            warn!("Failed to parse MCP message: {}", e);
+            let response = JsonRpcResponse::error(
+                Value::Null,
+                error_codes::PARSE_ERROR,
+                format!("Parse error: {}", e),
+            );
+            return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+        }
+    };
+
+    // Every message except `initialize` must belong to a live session
+    let is_initialize = message.get("method").and_then(Value::as_str) == Some("initialize");
+    if !is_initialize {
+        let Some(session_id) = session_id(&headers) else {
+            return (StatusCode::BAD_REQUEST, "Missing Mcp-Session-Id header").into_response();
+        };
+        if !state.sessions.touch(&session_id) {
+            return (StatusCode::NOT_FOUND, "Unknown or expired session").into_response();
+        }
+    }
+
+    // Git operations are blocking, keep them off the async workers
+    let server = Arc::clone(&state.server);
+    let session = session_id(&headers).unwrap_or_default();
+    let handled = tokio::task::spawn_blocking(move || server.handle_value(message, &session));
+    let response = match handled.await {
+        Ok(response) => response,
+        Err(e) => {
+            error!("MCP request handler panicked: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    // Notifications and client responses are acknowledged without a body
+    let Some(response) = response else {
+        return StatusCode::ACCEPTED.into_response();
+    };
+
+    let new_session = if is_initialize && response.error.is_none() {
+        let (session_id, dropped) = state.sessions.create();
+        for id in dropped {
+            state.server.end_session(&id);
+            info!("Expired MCP session {}", id);
+        }
+        info!("Created MCP session {}", session_id);
+        Some(session_id)
+    } else {
+        None
+    };
+
+    let mut http_response = if prefers_event_stream(&headers) {
+        sse_response(&response)
+    } else {
+        Json(response).into_response()
+    };
+
+    if let Some(session_id) = new_session {
+        if let Ok(value) = HeaderValue::from_str(&session_id) {
+            http_response.headers_mut().insert(SESSION_ID_HEADER, value);
+        }
+    }
+
+    http_response
+}
+
+/// Open an SSE stream carrying server notifications for a session
+async fn handle_get(State(state): State<HttpState>, headers: HeaderMap) -> Response {
+    if !origin_allowed(&state, &headers) {
+        return (StatusCode::FORBIDDEN, "Origin not allowed").into_response();
+    }
+    let Some(session_id) = session_id(&headers) else {
+        return (StatusCode::BAD_REQUEST, "Missing Mcp-Session-Id header").into_response();
+    };
+    let Some(closed) = state.sessions.closed(&session_id) else {
+        return (StatusCode::NOT_FOUND, "Unknown or expired session").into_response();
+    };
+
+    info!("Opened notification stream for MCP session {}", session_id);
+    let notifications = state.server.subscribe_notifications();
+
+    let stream = futures_util::stream::unfold(
+        (notifications, closed),
+        move |(mut notifications, mut closed)| {
+            let session_id = session_id.clone();
+            async move {
+                loop {
+                    let received = tokio::select! {
+                        // The sender is dropped when the session ends
+                        _ = closed.changed() => return None,
+                        received = notifications.recv() => received,
+                    };
+                    match received {
+                        Ok(notification) if notification.is_for(&session_id) => {
+                            let data =
+                                serde_json::to_string(&notification.message).unwrap_or_default();
+                            let event = Event::default().event("message").data(data);
+                            return Some((Ok::<_, Infallible>(event), (notifications, closed)));
+                        }
+                        Ok(_) => {}
+                        Err(RecvError::Lagged(skipped)) => {
+                            warn!("Dropped {} notifications for session {}", skipped, session_id)
+                        }
+                        Err(RecvError::Closed) => return None,
+                    }
+                }
+            }
+        },
+    );
+
+    Sse::new(stream).keep_alive(KeepAlive::default()).into_response()
+}
+
+/// Terminate a session at the client's request
+async fn handle_delete(State(state): State<HttpState>, headers: HeaderMap) -> Response {
+    if !origin_allowed(&state, &headers) {
+        return (StatusCode::FORBIDDEN, "Origin not allowed").into_response();
+    }
+    let Some(session_id) = session_id(&headers) else {
+        return (StatusCode::BAD_REQUEST, "Missing Mcp-Session-Id header").into_response();
+    };
+
+    if state.sessions.remove(&session_id) {
+        state.server.end_session(&session_id);
+        info!("Closed MCP session {}", session_id);
+        StatusCode::NO_CONTENT.into_response()
+    } else {
+        StatusCode::NOT_FOUND.into_response()
+    }
+}
+
+/// Whether a request's `Origin` is localhost or explicitly allowed
+///
+/// Guards against DNS rebinding: a page on another site that resolves to this machine
+/// still sends its own origin. Requests without an `Origin`, i.e. not from a browser,
+/// are let through.
+fn origin_allowed(state: &HttpState, headers: &HeaderMap) -> bool {
+    let Some(origin) = headers.get(header::ORIGIN) else {
+        return true;
+    };
+    let allowed = origin.to_str().is_ok_and(|origin| {
+        is_local_origin(origin) || state.allowed_origins.iter().any(|allowed| allowed == origin)
+    });
+    if !allowed {
+        warn!("Rejected MCP request from origin {:?}", origin);
+    }
+    allowed
+}
+
+/// Whether an origin like `http://localhost:5173` points at the loopback interface
+fn is_local_origin(origin: &str) -> bool {
+    let Some((_, authority)) = origin.split_once("://") else {
+        return false;
+    };
+    let host = match authority.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or(rest),
+        None => authority.split(':').next().unwrap_or(authority),
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "::1")
+}
+
+/// Extract the session id header, if present and valid
+fn session_id(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(SESSION_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+/// Whether the client asked for an SSE stream rather than a plain JSON body
+fn prefers_event_stream(headers: &HeaderMap) -> bool {
+    let accept = headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("");
+
+    accept.contains("text/event-stream") && !accept.contains("application/json")
+}
+
+/// Wrap a single JSON-RPC response in an SSE stream
+fn sse_response(response: &JsonRpcResponse) -> Response {
+    let data = serde_json::to_string(response).unwrap_or_default();
+    let event = Event::default().event("message").data(data);
+
+    Sse::new(futures_util::stream::once(async move {
+        Ok::<_, Infallible>(event)
+    }))
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idle_sessions_expire() {
+        let sessions = Sessions::new(Duration::from_millis(200), MAX_SESSIONS);
+        let (idle, _) = sessions.create();
+        let (streaming, _) = sessions.create();
+        let stream = sessions.closed(&streaming).expect("a live session");
+        std::thread::sleep(Duration::from_millis(400));
+
+        assert!(!sessions.touch(&idle));
+        // An open stream keeps its session alive
+        assert!(sessions.touch(&streaming));
+
+        let (_, dropped) = sessions.create();
+        assert_eq!(dropped, vec![idle]);
+        drop(stream);
+    }
+
+    #[test]
+    fn least_recently_used_session_makes_room() {
+        let sessions = Sessions::new(SESSION_IDLE_TIMEOUT, 2);
+        let (first, _) = sessions.create();
+        let (second, _) = sessions.create();
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(sessions.touch(&first));
+
+        let (third, dropped) = sessions.create();
+        assert_eq!(dropped, vec![second.clone()]);
+        assert!(sessions.touch(&first));
+        assert!(sessions.touch(&third));
+        assert!(!sessions.touch(&second));
+    }
+
+    #[tokio::test]
+    async fn ending_a_session_closes_its_streams() {
+        let sessions = Sessions::new(SESSION_IDLE_TIMEOUT, MAX_SESSIONS);
+        let (id, _) = sessions.create();
+        let mut closed = sessions.closed(&id).expect("a live session");
+
+        assert!(sessions.remove(&id));
+        assert!(closed.changed().await.is_err());
+        assert!(sessions.closed(&id).is_none());
+        assert!(!sessions.remove(&id));
+    }
+}
@@ -0,0 +1,425 @@
+pub mod http;
+pub mod prompts;
+pub mod resources;
+pub mod stdio;
+pub mod tools;
+pub mod watcher;
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+use crate::utils::{git_helpers, repo_registry};
+use crate::models::mcp::{
+    error_codes, CallToolParams, JsonRpcNotification, Resource, GetPromptParams, Implementation, InitializeParams,
+    InitializeResult, JsonRpcRequest, JsonRpcResponse, ListPromptsResult,
+    ListResourceTemplatesResult, ListResourcesResult, ListToolsResult, PromptsCapability,
+    ResourceUriParams, ResourcesCapability, ServerCapabilities, ToolsCapability, JSONRPC_VERSION,
+    SUPPORTED_PROTOCOL_VERSIONS,
+};
+
+/// Capacity of the notification channel shared by all connected clients
+const NOTIFICATION_CAPACITY: usize = 64;
+
+/// A server notification and the session it is addressed to
+#[derive(Debug, Clone)]
+pub struct Notification {
+    /// `None` for notifications every session should receive
+    pub session: Option<String>,
+    pub message: JsonRpcNotification,
+}
+
+impl Notification {
+    /// Whether a session should receive this notification
+    pub fn is_for(&self, session: &str) -> bool {
+        self.session.as_deref().is_none_or(|target| target == session)
+    }
+}
+
+/// Transport-independent MCP request dispatcher
+///
+/// Transports feed raw JSON-RPC messages in, tagged with the session they arrived on, and
+/// write back whatever response comes out. Server-initiated notifications are broadcast to
+/// every transport, which forwards those addressed to its sessions.
+pub struct McpServer {
+    tools: tools::ToolRegistry,
+    notifications: broadcast::Sender<Notification>,
+    /// Resource URIs passed to `resources/subscribe`, per session
+    subscriptions: Mutex<HashMap<String, HashSet<String>>>,
+    /// `resources/list` entries per repository, with the HEAD they were computed for
+    resource_cache: Mutex<HashMap<String, (String, Vec<Resource>)>>,
+}
+
+impl Default for McpServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl McpServer {
+    pub fn new() -> Self {
+        let (notifications, _) = broadcast::channel(NOTIFICATION_CAPACITY);
+
+        McpServer {
+            tools: tools::ToolRegistry::with_default_tools(),
+            notifications,
+            subscriptions: Mutex::new(HashMap::new()),
+            resource_cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Receive the notifications this server emits
+    pub fn subscribe_notifications(&self) -> broadcast::Receiver<Notification> {
+        self.notifications.subscribe()
+    }
+
+    /// Forget everything kept for a session once it has ended
+    pub fn end_session(&self, session: &str) {
+        self.subscriptions.lock().unwrap().remove(session);
+    }
+
+    /// React to references moving in the repository
+    ///
+    /// Drops data cached for the old HEAD and tells clients to refetch
+    pub fn refs_changed(&self, repo_name: &str, head_moved: bool) {
+        if head_moved {
+            self.resource_cache.lock().unwrap().remove(repo_name);
+        }
+
+        let prefix = format!("git://{}/", repo_name);
+        let subscribed: Vec<(String, String)> = self
+            .subscriptions
+            .lock()
+            .unwrap()
+            .iter()
+            .flat_map(|(session, uris)| {
+                uris.iter()
+                    .filter(|uri| uri.starts_with(&prefix))
+                    .map(move |uri| (session.clone(), uri.clone()))
+            })
+            .collect();
+        for (session, uri) in subscribed {
+            self.notify(
+                Some(session),
+                "notifications/resources/updated",
+                Some(json!({ "uri": uri })),
+            );
+        }
+        self.notify(None, "notifications/resources/list_changed", None);
+    }
+
+    /// Broadcast a notification; dropped silently when no client is listening
+    fn notify(&self, session: Option<String>, method: &str, params: Option<Value>) {
+        debug!("Sending notification: {}", method);
+        let _ = self.notifications.send(Notification {
+            session,
+            message: JsonRpcNotification::new(method, params),
+        });
+    }
+
+    /// Handle one raw JSON-RPC message received on `session`
+    ///
+    /// Returns `None` for notifications and for responses sent by the client
+    pub fn handle_message(&self, raw: &str, session: &str) -> Option<JsonRpcResponse> {
+        let value: Value = match serde_json::from_str(raw) {
+            Ok(value) => value,
+            Err(e) => {
+                warn!("Failed to parse JSON-RPC message: {}", e);
+                return Some(JsonRpcResponse::error(
+                    Value::Null,
+                    error_codes::PARSE_ERROR,
+                    format!("Parse error: {}", e),
+                ));
+            }
+        };
+
+        self.handle_value(value, session)
+    }
+
+    /// Handle one already-parsed JSON-RPC message
+    pub fn handle_value(&self, value: Value, session: &str) -> Option<JsonRpcResponse> {
+        // Responses to server-initiated requests carry no method; we never send any
+        if value.get("method").is_none() && value.get("id").is_some() {
+            debug!("Ignoring JSON-RPC response from client");
+            return None;
+        }
+
+        match serde_json::from_value::<JsonRpcRequest>(value) {
+            Ok(request) => self.handle_request(request, session),
+            Err(e) => Some(JsonRpcResponse::error(
+                Value::Null,
+                error_codes::INVALID_REQUEST,
+                format!("Invalid request: {}", e),
+            )),
+        }
+    }
+
+    /// Handle a parsed JSON-RPC request or notification
+    pub fn handle_request(&self, request: JsonRpcRequest, session: &str) -> Option<JsonRpcResponse> {
+        let Some(id) = request.id else {
+            debug!("Received notification: {}", request.method);
+            return None;
+        };
+
+        if request.jsonrpc != JSONRPC_VERSION {
+            return Some(JsonRpcResponse::error(
+                id,
+                error_codes::INVALID_REQUEST,
+                format!("Unsupported JSON-RPC version: {}", request.jsonrpc),
+            ));
+        }
+
+        debug!("Received request: {}", request.method);
+        let params = request.params.unwrap_or(Value::Null);
+
+        let result = match request.method.as_str() {
+            "initialize" => parse_params(params).map(|params| self.initialize(params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(to_value(ListToolsResult {
+                tools: self.tools.list(),
+            })),
+            "tools/call" => parse_params(params).and_then(|params| self.call_tool(params)),
+            "resources/list" => self.list_resources(),
+            "resources/templates/list" => Ok(to_value(ListResourceTemplatesResult {
+                resource_templates: resources::list_templates(),
+            })),
+            "prompts/list" => Ok(to_value(ListPromptsResult {
+                prompts: prompts::list_prompts(),
+            })),
+            "prompts/get" => parse_params(params).and_then(|params| self.get_prompt(params)),
+            "resources/subscribe" => {
+                parse_params(params).map(|params: ResourceUriParams| {
+                    self.subscriptions
+                        .lock()
+                        .unwrap()
+                        .entry(session.to_string())
+                        .or_default()
+                        .insert(params.uri);
+                    json!({})
+                })
+            }
+            "resources/unsubscribe" => {
+                parse_params(params).map(|params: ResourceUriParams| {
+                    if let Some(uris) = self.subscriptions.lock().unwrap().get_mut(session) {
+                        uris.remove(&params.uri);
+                    }
+                    json!({})
+                })
+            }
+            "resources/read" => parse_params(params).and_then(|params| self.read_resource(params)),
+            method => Err((
+                error_codes::METHOD_NOT_FOUND,
+                format!("Method not found: {}", method),
+            )),
+        };
+
+        Some(match result {
+            Ok(result) => JsonRpcResponse::success(id, result),
+            Err((code, message)) => JsonRpcResponse::error(id, code, message),
+        })
+    }
+
+    /// Negotiate the protocol version and advertise our capabilities
+    fn initialize(&self, params: InitializeParams) -> Value {
+        if let Some(client) = &params.client_info {
+            info!("MCP client connected: {} {}", client.name, client.version);
+        }
+
+        // Echo the client's version if we support it, otherwise offer our newest
+        let protocol_version = SUPPORTED_PROTOCOL_VERSIONS
+            .iter()
+            .find(|version| **version == params.protocol_version)
+            .unwrap_or(&SUPPORTED_PROTOCOL_VERSIONS[0])
+            .to_string();
+
+        to_value(InitializeResult {
+            protocol_version,
+            capabilities: ServerCapabilities {
+                tools: ToolsCapability { list_changed: false },
+                // The prompt list is fixed, so it never changes
+                prompts: PromptsCapability { list_changed: false },
+                resources: ResourcesCapability {
+                    subscribe: true,
+                    list_changed: true,
+                },
+            },
+            server_info: Implementation {
+                name: "git-time-machine".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+            },
+            instructions: Some(
+                "Use these tools to explore Git history, diffs, and file evolution over time."
+                    .to_string(),
+            ),
+        })
+    }
+
+    /// Dispatch a `tools/call` request to the matching tool
+    fn call_tool(&self, params: CallToolParams) -> Result<Value, (i64, String)> {
+        info!("Processing tools/call request for tool: {}", params.name);
+
+        let arguments = params.arguments.unwrap_or_else(|| json!({}));
+        match self.tools.call(&params.name, arguments) {
+            Ok(result) => Ok(to_value(result)),
+            Err(e @ tools::ToolError::Serialization(_)) => {
+                Err((error_codes::INTERNAL_ERROR, e.to_string()))
+            }
+            Err(e) => Err((error_codes::INVALID_PARAMS, e.to_string())),
+        }
+    }
+
+    /// List recent commits as concrete resources
+    fn list_resources(&self) -> Result<Value, (i64, String)> {
+        let internal = |e: resources::ResourceError| (error_codes::INTERNAL_ERROR, e.to_string());
+        let mut all = Vec::new();
+
+        for (name, _) in repo_registry::registry().iter() {
+            let repo = repo_registry::open(Some(name)).map_err(|e| internal(e.into()))?;
+            // Repositories without commits have nothing to list
+            let Ok(head) = git_helpers::get_head_commit(&repo).map(|commit| commit.sha) else {
+                continue;
+            };
+
+            let mut cache = self.resource_cache.lock().unwrap();
+            match cache.get(name) {
+                Some((cached_head, resources)) if *cached_head == head => {
+                    all.extend(resources.iter().cloned())
+                }
+                _ => {
+                    let resources = resources::list_resources(name).map_err(internal)?;
+                    all.extend(resources.iter().cloned());
+                    cache.insert(name.to_string(), (head, resources));
+                }
+            }
+        }
+
+        Ok(to_value(ListResourcesResult { resources: all }))
+    }
+
+    /// Resolve a `resources/read` request
+    fn read_resource(&self, params: ResourceUriParams) -> Result<Value, (i64, String)> {
+        resources::read_resource(&params.uri)
+            .map(to_value)
+            .map_err(|e| match e {
+                resources::ResourceError::InvalidUri(_) => {
+                    (error_codes::INVALID_PARAMS, e.to_string())
+                }
+                resources::ResourceError::Serialization(_) => {
+                    (error_codes::INTERNAL_ERROR, e.to_string())
+                }
+                _ => (error_codes::RESOURCE_NOT_FOUND, e.to_string()),
+            })
+    }
+    /// Render a `prompts/get` request
+    fn get_prompt(&self, params: GetPromptParams) -> Result<Value, (i64, String)> {
+        prompts::get_prompt(&params.name, &params.arguments)
+            .map(to_value)
+            .map_err(|e| match e {
+                prompts::PromptError::Git(_) => (error_codes::INTERNAL_ERROR, e.to_string()),
+                _ => (error_codes::INVALID_PARAMS, e.to_string()),
+            })
+    }
+}
+
+/// Deserialize request params into the expected type
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, (i64, String)> {
+    serde_json::from_value(params).map_err(|e| {
+        (
+            error_codes::INVALID_PARAMS,
+            format!("Invalid params: {}", e),
+        )
+    })
+}
+
+/// Serialize a result type that cannot fail to serialize
+fn to_value<T: Serialize>(value: T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Send one raw message on a test session
+    fn handle(server: &McpServer, raw: &str) -> Option<JsonRpcResponse> {
+        server.handle_message(raw, "test")
+    }
+
+    /// The error code of a response that must be an error
+    fn error_code(response: Option<JsonRpcResponse>) -> i64 {
+        response.expect("a response").error.expect("an error").code
+    }
+
+    #[test]
+    fn unknown_methods_are_reported() {
+        let server = McpServer::new();
+        let response = handle(&server, r#"{"jsonrpc":"2.0","id":1,"method":"nope"}"#);
+        assert_eq!(error_code(response), error_codes::METHOD_NOT_FOUND);
+    }
+
+    #[test]
+    fn malformed_messages_are_rejected() {
+        let server = McpServer::new();
+
+        let response = handle(&server, "{not json").expect("a response");
+        assert_eq!(response.id, Value::Null);
+        assert_eq!(response.error.expect("an error").code, error_codes::PARSE_ERROR);
+
+        let response = handle(&server, r#"{"jsonrpc":"2.0","id":1,"method":7}"#);
+        assert_eq!(error_code(response), error_codes::INVALID_REQUEST);
+
+        let response = handle(&server, r#"{"jsonrpc":"1.0","id":1,"method":"ping"}"#);
+        assert_eq!(error_code(response), error_codes::INVALID_REQUEST);
+    }
+
+    #[test]
+    fn invalid_params_are_reported() {
+        let server = McpServer::new();
+
+        let response =
+            handle(&server, r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{}}"#);
+        assert_eq!(error_code(response), error_codes::INVALID_PARAMS);
+
+        let response = handle(
+            &server,
+            r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"missing"}}"#,
+        );
+        assert_eq!(error_code(response), error_codes::INVALID_PARAMS);
+
+        let response = handle(
+            &server,
+            r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"get_git_blame","arguments":{"file":7}}}"#,
+        );
+        assert_eq!(error_code(response), error_codes::INVALID_PARAMS);
+    }
+
+    #[test]
+    fn notifications_and_client_responses_get_no_reply() {
+        let server = McpServer::new();
+        assert!(handle(&server, r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#).is_none());
+        assert!(handle(&server, r#"{"jsonrpc":"2.0","method":"nope"}"#).is_none());
+        assert!(handle(&server, r#"{"jsonrpc":"2.0","id":4,"result":{}}"#).is_none());
+    }
+
+    #[test]
+    fn requests_echo_their_id() {
+        let server = McpServer::new();
+        let response = handle(&server, r#"{"jsonrpc":"2.0","id":"abc","method":"ping"}"#)
+            .expect("a response");
+        assert_eq!(response.id, json!("abc"));
+        assert_eq!(response.result, Some(json!({})));
+
+        let response = handle(
+            &server,
+            r#"{"jsonrpc":"2.0","id":5,"method":"initialize","params":{"protocolVersion":"2025-06-18"}}"#,
+        )
+        .expect("a response");
+        let result = response.result.expect("a result");
+        assert_eq!(result["protocolVersion"], "2025-06-18");
+        assert_eq!(result["capabilities"]["resources"]["subscribe"], true);
+        assert_eq!(result["capabilities"]["tools"]["listChanged"], false);
+    }
+}
@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use thiserror::Error;
+use tracing::info;
+
+use crate::{
+    models::mcp::{Content, GetPromptResult, Prompt, PromptArgument, PromptMessage, Role},
+    utils::{
+        git_helpers::{self, BlameQuery, FileChangeInfo, GitError, HistoryQuery, SimpleCommit},
+        repo_registry,
+    },
+};
+
+/// Maximum number of characters of diff embedded per file change
+const MAX_DIFF_CHARS: usize = 8_000;
+
+/// Maximum number of commits listed by `changes_since`
+const MAX_RANGE_COMMITS: usize = 100;
+
+/// Default number of commits walked by `file_evolution`
+const DEFAULT_EVOLUTION_COMMITS: usize = 10;
+
+/// Errors raised while rendering a prompt
+#[derive(Error, Debug)]
+pub enum PromptError {
+    #[error("Unknown prompt: {0}")]
+    UnknownPrompt(String),
+    #[error("Missing required argument: {0}")]
+    MissingArgument(&'static str),
+    #[error("Invalid argument '{0}': {1}")]
+    InvalidArgument(&'static str, String),
+    #[error(transparent)]
+    Git(#[from] GitError),
+}
+
+/// List the prompt templates this server offers
+pub fn list_prompts() -> Vec<Prompt> {
+    vec![
+        Prompt {
+            name: "explain_line".to_string(),
+            description: "Explain why a line exists, using the blame and diff of the commit that last changed it".to_string(),
+            arguments: vec![
+                argument("file", "Path to the file", true),
+                argument("line", "Line number (1-indexed)", true),
+                repo_argument(),
+            ],
+        },
+        Prompt {
+            name: "changes_since".to_string(),
+            description: "Summarize what changed between a past commit (e.g. a release) and HEAD".to_string(),
+            arguments: vec![
+                argument("since", "Revision to compare from (SHA, tag, branch, ...)", true),
+                argument("head", "Revision to compare to (defaults to HEAD)", false),
+                repo_argument(),
+            ],
+        },
+        Prompt {
+            name: "file_evolution".to_string(),
+            description: "Summarize how a file evolved over its recent commits".to_string(),
+            arguments: vec![
+                argument("file", "Path to the file", true),
+                argument("limit", "Number of commits to include (defaults to 10)", false),
+                repo_argument(),
+            ],
+        },
+    ]
+}
+
+/// Render a prompt, pre-fetching the history it needs
+pub fn get_prompt(
+    name: &str,
+    arguments: &HashMap<String, String>,
+) -> Result<GetPromptResult, PromptError> {
+    info!("Rendering prompt: {}", name);
+
+    let (description, text) = match name {
+        "explain_line" => explain_line(arguments)?,
+        "changes_since" => changes_since(arguments)?,
+        "file_evolution" => file_evolution(arguments)?,
+        _ => return Err(PromptError::UnknownPrompt(name.to_string())),
+    };
+
+    Ok(GetPromptResult {
+        description,
+        messages: vec![PromptMessage {
+            role: Role::User,
+            content: Content::Text { text },
+        }],
+    })
+}
+
+/// "Why does this line exist?"
+fn explain_line(arguments: &HashMap<String, String>) -> Result<(String, String), PromptError> {
+    let file = required(arguments, "file")?;
+    let line: usize = parse(arguments, "line")?.ok_or(PromptError::MissingArgument("line"))?;
+
+    let repo = open_repo(arguments)?;
+
+    let query = BlameQuery {
+        start_line: Some(line),
+        end_line: Some(line),
+        ..Default::default()
+    };
+    let blamed = git_helpers::get_file_blame(&repo, file, &query)
+        .map_err(|e| match e {
+            GitError::InvalidArgument(_) => {
+                PromptError::InvalidArgument("line", format!("{} has no line {}", file, line))
+            }
+            e => e.into(),
+        })?
+        .lines
+        .into_iter()
+        .next()
+        .ok_or_else(|| {
+            PromptError::InvalidArgument("line", format!("{} has no line {}", file, line))
+        })?;
+
+    let diff = git_helpers::get_commit_diff(&repo, &blamed.commit_sha)?;
+    let file_diff = diff
+        .changes
+        .iter()
+        .find(|change| change.path == blamed.orig_path)
+        .and_then(|change| change.diff.as_deref())
+        .unwrap_or("");
+
+    let mut text = String::new();
+    let _ = writeln!(text, "Explain why line {} of `{}` exists.\n", line, file);
+    let _ = writeln!(
+        text,
+        "The line currently reads:\n```\n{}\n```\n",
+        blamed.content
+    );
+    let _ = writeln!(
+        text,
+        "It was last changed in commit {} by {} <{}> on {}:\n\n{}\n",
+        blamed.commit_sha,
+        blamed.author,
+        blamed.email,
+        blamed.timestamp,
+        blamed.commit_message.trim()
+    );
+    let _ = writeln!(
+        text,
+        "The change that commit made to `{}`:\n```diff\n{}\n```\n",
+        blamed.orig_path,
+        truncate(file_diff, MAX_DIFF_CHARS)
+    );
+    text.push_str(
+        "Using the commit message and diff, explain what problem this line solves and why it was \
+         introduced. If the commit only reformatted or moved the line, say so and suggest looking \
+         further back in the file's history.",
+    );
+
+    Ok((format!("Explain line {} of {}", line, file), text))
+}
+
+/// "What changed since release X?"
+fn changes_since(arguments: &HashMap<String, String>) -> Result<(String, String), PromptError> {
+    let since = required(arguments, "since")?;
+    let repo = open_repo(arguments)?;
+    let head = match arguments.get("head") {
+        Some(head) => head.clone(),
+        None => git_helpers::get_head_commit(&repo)?.sha,
+    };
+
+    let commits = git_helpers::get_commits_in_range(&repo, since, &head, MAX_RANGE_COMMITS)?;
+    let summary = git_helpers::summarize_diff(&repo, since, &head)?;
+
+    let mut text = String::new();
+    let _ = writeln!(
+        text,
+        "Summarize what changed between {} and {}.\n",
+        since, head
+    );
+    let _ = writeln!(text, "{}\n", summary.summary);
+
+    let _ = writeln!(text, "Commits ({} shown, newest first):", commits.len());
+    for commit in &commits {
+        let _ = writeln!(text, "- {}", commit_line(commit));
+    }
+
+    let _ = writeln!(text, "\nFiles changed:");
+    for change in &summary.changes {
+        let _ = writeln!(text, "- {}", change_line(change));
+    }
+
+    text.push_str(
+        "\nGroup related commits into themes, highlight user-visible and breaking changes, and \
+         point out anything that looks risky or incomplete.",
+    );
+
+    Ok((format!("Changes since {}", since), text))
+}
+
+/// "Summarize this file's evolution"
+fn file_evolution(arguments: &HashMap<String, String>) -> Result<(String, String), PromptError> {
+    let file = required(arguments, "file")?;
+    let limit = parse(arguments, "limit")?.unwrap_or(DEFAULT_EVOLUTION_COMMITS);
+    let repo = open_repo(arguments)?;
+
+    let query = HistoryQuery {
+        limit: Some(limit),
+        follow: true,
+        ..Default::default()
+    };
+    let commits = git_helpers::get_commits_affecting_file(&repo, file, &query)?.commits;
+
+    let mut text = String::new();
+    let _ = writeln!(
+        text,
+        "Summarize how `{}` evolved over its last {} commits, oldest first.\n",
+        file,
+        commits.len()
+    );
+
+    for entry in commits.iter().rev() {
+        let diff = git_helpers::get_commit_diff(&repo, &entry.commit.sha)?;
+        let file_diff = diff
+            .changes
+            .iter()
+            .find(|change| change.path == entry.path)
+            .and_then(|change| change.diff.as_deref())
+            .unwrap_or("");
+
+        let _ = writeln!(text, "## {}\n", commit_line(&entry.commit));
+        let _ = writeln!(
+            text,
+            "```diff\n{}\n```\n",
+            truncate(file_diff, MAX_DIFF_CHARS)
+        );
+    }
+
+    text.push_str(
+        "Describe the major phases of the file's history, why each change was made according \
+         to its commit message, and how the file's responsibilities shifted over time.",
+    );
+
+    Ok((format!("Evolution of {}", file), text))
+}
+
+fn argument(name: &str, description: &str, required: bool) -> PromptArgument {
+    PromptArgument {
+        name: name.to_string(),
+        description: description.to_string(),
+        required,
+    }
+}
+
+fn repo_argument() -> PromptArgument {
+    argument(
+        "repo",
+        "Name of the repository (defaults to the server's default repository)",
+        false,
+    )
+}
+
+/// Open the repository named by the optional `repo` argument
+fn open_repo(arguments: &HashMap<String, String>) -> Result<git2::Repository, PromptError> {
+    Ok(repo_registry::open(arguments.get("repo").map(String::as_str))?)
+}
+
+fn required<'a>(
+    arguments: &'a HashMap<String, String>,
+    name: &'static str,
+) -> Result<&'a str, PromptError> {
+    arguments
+        .get(name)
+        .map(String::as_str)
+        .ok_or(PromptError::MissingArgument(name))
+}
+
+fn parse(
+    arguments: &HashMap<String, String>,
+    name: &'static str,
+) -> Result<Option<usize>, PromptError> {
+    arguments
+        .get(name)
+        .map(|value| {
+            value.parse().map_err(|_| {
+                PromptError::InvalidArgument(name, format!("'{}' is not a number", value))
+            })
+        })
+        .transpose()
+}
+
+/// One-line description of a commit: short SHA, subject, author and date
+fn commit_line(commit: &SimpleCommit) -> String {
+    format!(
+        "{} {} ({}, {})",
+        git_helpers::short_sha(&commit.sha),
+        commit.message.lines().next().unwrap_or(""),
+        commit.author,
+        commit.timestamp
+    )
+}
+
+/// One-line description of a file change
+fn change_line(change: &FileChangeInfo) -> String {
+    format!(
+        "{} [{:?}] +{} -{}",
+        change.path, change.change_type, change.additions, change.deletions
+    )
+}
+
+/// Cut text to at most `max` characters, marking the cut
+fn truncate(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((idx, _)) => format!("{}\n... (truncated)", &text[..idx]),
+        None => text.to_string(),
+    }
+}
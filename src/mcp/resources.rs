@@ -0,0 +1,256 @@
+use serde::Serialize;
+use thiserror::Error;
+use tracing::info;
+
+use crate::{
+    handlers::{blame, diff},
+    models::{
+        mcp::{ReadResourceResult, Resource, ResourceContents, ResourceTemplate},
+        tool_schema::{BlameRequest, CommitDiffRequest},
+    },
+    utils::{
+        git_helpers::{self, GitError},
+        repo_registry,
+    },
+};
+
+/// Number of recent commits advertised by `resources/list`
+const LISTED_COMMITS: usize = 20;
+
+const JSON_MIME_TYPE: &str = "application/json";
+const TEXT_MIME_TYPE: &str = "text/plain";
+
+/// Errors raised while resolving a resource URI
+#[derive(Error, Debug)]
+pub enum ResourceError {
+    #[error("Invalid resource URI: {0}")]
+    InvalidUri(String),
+    #[error(transparent)]
+    Git(#[from] GitError),
+    #[error("Failed to serialize resource: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+/// A parsed `git://` resource URI
+#[derive(Debug, PartialEq)]
+enum GitResource {
+    /// `git://{repo}/blob/{rev}/{path}`
+    Blob { rev: String, path: String },
+    /// `git://{repo}/commit/{sha}`
+    Commit { sha: String },
+    /// `git://{repo}/blame/{rev}/{path}`
+    Blame { rev: String, path: String },
+}
+
+/// List the resource templates this server can resolve
+pub fn list_templates() -> Vec<ResourceTemplate> {
+    vec![
+        ResourceTemplate {
+            uri_template: "git://{repo}/blob/{rev}/{path}".to_string(),
+            name: "File at revision".to_string(),
+            description: "Contents of a file as it existed at a commit".to_string(),
+            mime_type: Some(TEXT_MIME_TYPE.to_string()),
+        },
+        ResourceTemplate {
+            uri_template: "git://{repo}/commit/{sha}".to_string(),
+            name: "Commit".to_string(),
+            description: "Metadata and full diff of a commit".to_string(),
+            mime_type: Some(JSON_MIME_TYPE.to_string()),
+        },
+        ResourceTemplate {
+            uri_template: "git://{repo}/blame/{rev}/{path}".to_string(),
+            name: "File blame".to_string(),
+            description: "Line-by-line blame of a file as of a revision".to_string(),
+            mime_type: Some(JSON_MIME_TYPE.to_string()),
+        },
+    ]
+}
+
+/// List concrete resources for one repository, one per recent commit on HEAD
+pub fn list_resources(repo_name: &str) -> Result<Vec<Resource>, ResourceError> {
+    let repo = repo_registry::open(Some(repo_name))?;
+    let commits = git_helpers::get_recent_commits(&repo, LISTED_COMMITS)?;
+
+    Ok(commits
+        .into_iter()
+        .map(|commit| Resource {
+            uri: format!("git://{}/commit/{}", repo_name, commit.sha),
+            name: format!(
+                "{} {}",
+                git_helpers::short_sha(&commit.sha),
+                commit.message.lines().next().unwrap_or("")
+            ),
+            description: Some(format!("Commit by {} at {}", commit.author, commit.timestamp)),
+            mime_type: Some(JSON_MIME_TYPE.to_string()),
+        })
+        .collect())
+}
+
+/// Read the resource identified by a `git://` URI
+pub fn read_resource(uri: &str) -> Result<ReadResourceResult, ResourceError> {
+    info!("Reading resource: {}", uri);
+
+    let (repo_name, resource) = parse_uri(uri)?;
+    let repo = repo_registry::open(Some(&repo_name))?;
+
+    let (mime_type, text) = match resource {
+        GitResource::Blob { rev, path } => {
+            let file = git_helpers::get_file_at_commit(&repo, &path, &rev)?;
+            (TEXT_MIME_TYPE, file.content)
+        }
+        GitResource::Commit { sha } => {
+            let response = diff::commit_diff_response(CommitDiffRequest {
+                sha: Some(sha),
+                as_of: None,
+                repo: Some(repo_name),
+            })?;
+            (JSON_MIME_TYPE, to_json(&response)?)
+        }
+        GitResource::Blame { rev, path } => {
+            let response = blame::blame_response(BlameRequest {
+                file: path,
+                rev: Some(rev),
+                repo: Some(repo_name),
+                ..Default::default()
+            })?;
+            (JSON_MIME_TYPE, to_json(&response)?)
+        }
+    };
+
+    Ok(ReadResourceResult {
+        contents: vec![ResourceContents {
+            uri: uri.to_string(),
+            mime_type: Some(mime_type.to_string()),
+            text,
+        }],
+    })
+}
+
+/// Split a `git://` URI into its repository and resource parts
+///
+/// Revisions containing `/` must be percent-encoded (`feature%2Fx`)
+fn parse_uri(uri: &str) -> Result<(String, GitResource), ResourceError> {
+    let invalid = || ResourceError::InvalidUri(uri.to_string());
+
+    let rest = uri.strip_prefix("git://").ok_or_else(invalid)?;
+    let (repo, rest) = rest.split_once('/').ok_or_else(invalid)?;
+    let (kind, rest) = rest.split_once('/').ok_or_else(invalid)?;
+
+    let resource = match kind {
+        "commit" if !rest.is_empty() => GitResource::Commit {
+            sha: percent_decode(rest).ok_or_else(invalid)?,
+        },
+        "blob" | "blame" => {
+            let (rev, path) = rest.split_once('/').ok_or_else(invalid)?;
+            let rev = percent_decode(rev).ok_or_else(invalid)?;
+            let path = percent_decode(path).ok_or_else(invalid)?;
+            if rev.is_empty() || path.is_empty() {
+                return Err(invalid());
+            }
+
+            if kind == "blob" {
+                GitResource::Blob { rev, path }
+            } else {
+                GitResource::Blame { rev, path }
+            }
+        }
+        _ => return Err(invalid()),
+    };
+
+    Ok((percent_decode(repo).ok_or_else(invalid)?, resource))
+}
+
+/// Decode `%XX` escapes in a URI component
+fn percent_decode(input: &str) -> Option<String> {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = input.get(i + 1..i + 3)?;
+            // `from_str_radix` alone would also take a sign, as in `%+f`
+            if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return None;
+            }
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8(decoded).ok()
+}
+
+/// Serialize a response body for a JSON resource
+fn to_json<T: Serialize>(value: &T) -> Result<String, ResourceError> {
+    Ok(serde_json::to_string_pretty(value)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blob(rev: &str, path: &str) -> GitResource {
+        GitResource::Blob {
+            rev: rev.to_string(),
+            path: path.to_string(),
+        }
+    }
+
+    #[test]
+    fn percent_decoding() {
+        assert_eq!(percent_decode("feature%2Fx").as_deref(), Some("feature/x"));
+        assert_eq!(percent_decode("a%20b%2fc").as_deref(), Some("a b/c"));
+        assert_eq!(percent_decode("plain").as_deref(), Some("plain"));
+        assert_eq!(percent_decode("caf%C3%A9").as_deref(), Some("café"));
+
+        for bad in ["%", "%2", "abc%", "%zz", "%+f", "%-1", "%C3"] {
+            assert_eq!(percent_decode(bad), None, "{bad}");
+        }
+    }
+
+    #[test]
+    fn parses_resource_uris() {
+        let (repo, resource) = parse_uri("git://app/blob/feature%2Fx/src/main.rs").unwrap();
+        assert_eq!(repo, "app");
+        assert_eq!(resource, blob("feature/x", "src/main.rs"));
+
+        let (_, resource) = parse_uri("git://app/blame/HEAD~2/a%20b.txt").unwrap();
+        assert_eq!(
+            resource,
+            GitResource::Blame {
+                rev: "HEAD~2".to_string(),
+                path: "a b.txt".to_string(),
+            }
+        );
+
+        let (repo, resource) = parse_uri("git://my%20repo/commit/abc123").unwrap();
+        assert_eq!(repo, "my repo");
+        assert_eq!(resource, GitResource::Commit { sha: "abc123".to_string() });
+    }
+
+    #[test]
+    fn rejects_malformed_uris() {
+        for uri in [
+            "file://app/blob/HEAD/a.rs",
+            "git://app",
+            "git://app/blob",
+            "git://app/tree/HEAD/a.rs",
+            "git://app/commit/",
+            "git://app/blob/HEAD",
+            "git://app/blob//a.rs",
+            "git://app/blob/HEAD/",
+            "git://app/blame/%2/a.rs",
+            "git://app/blob/HEAD/a%zz.rs",
+            "git://app%/commit/abc",
+        ] {
+            assert!(
+                matches!(parse_uri(uri), Err(ResourceError::InvalidUri(ref bad)) if bad == uri),
+                "{uri}"
+            );
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use serde::Serialize;
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Stdout};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{error, info, warn};
+
+use super::McpServer;
+use crate::models::mcp::{error_codes, JsonRpcResponse};
+
+/// Session id for the single client on stdio
+pub const STDIO_SESSION: &str = "stdio";
+
+/// Serve MCP over stdin/stdout using newline-delimited JSON-RPC
+///
+/// Runs until the client closes stdin. Server notifications are interleaved
+/// with responses on stdout.
+pub async fn serve(server: Arc<McpServer>) -> anyhow::Result<()> {
+    info!("Serving MCP over stdio");
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+    let mut notifications = server.subscribe_notifications();
+
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                let Some(line) = line? else { break };
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                let response = dispatch(Arc::clone(&server), line).await;
+                if let Some(response) = response {
+                    write_message(&mut stdout, &response).await?;
+                }
+            }
+            notification = notifications.recv() => match notification {
+                Ok(notification) if notification.is_for(STDIO_SESSION) => {
+                    write_message(&mut stdout, &notification.message).await?
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => warn!("Dropped {} notifications", skipped),
+                Err(RecvError::Closed) => {}
+            },
+        }
+    }
+
+    server.end_session(STDIO_SESSION);
+    info!("stdin closed, shutting down");
+    Ok(())
+}
+
+/// Handle one line from stdin on a blocking worker
+///
+/// A panicking handler is answered with an internal error instead of ending the session.
+async fn dispatch(server: Arc<McpServer>, line: String) -> Option<JsonRpcResponse> {
+    // Keep the id around so a panicking handler can still be answered
+    let id = serde_json::from_str::<Value>(&line)
+        .ok()
+        .and_then(|value| value.get("id").cloned());
+
+    // Git operations are blocking, keep them off the async workers
+    let handled = tokio::task::spawn_blocking(move || server.handle_message(&line, STDIO_SESSION));
+    match handled.await {
+        Ok(response) => response,
+        Err(e) => {
+            error!("MCP request handler panicked: {}", e);
+            id.map(|id| JsonRpcResponse::error(id, error_codes::INTERNAL_ERROR, "Internal error"))
+        }
+    }
+}
+
+/// Write one JSON-RPC message as a single line
+async fn write_message<T: Serialize>(stdout: &mut Stdout, message: &T) -> anyhow::Result<()> {
+    let mut output = serde_json::to_string(message)?;
+    output.push('\n');
+    stdout.write_all(output.as_bytes()).await?;
+    stdout.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::tool_schema::{ListRepositoriesRequest, ListRepositoriesResponse};
+    use crate::utils::git_helpers::GitError;
+
+    fn explode(_: ListRepositoriesRequest) -> Result<ListRepositoriesResponse, GitError> {
+        panic!("handler bug")
+    }
+
+    #[tokio::test]
+    async fn panicking_handler_is_an_internal_error() {
+        let mut server = McpServer::new();
+        server.tools.register("explode", explode);
+        let server = Arc::new(server);
+
+        let line = r#"{"jsonrpc":"2.0","id":7,"method":"tools/call","params":{"name":"explode"}}"#;
+        let response = dispatch(Arc::clone(&server), line.to_string())
+            .await
+            .expect("a response for a request");
+        assert_eq!(response.id, 7);
+        assert_eq!(response.error.expect("an error").code, error_codes::INTERNAL_ERROR);
+
+        // The server keeps answering afterwards
+        let line = r#"{"jsonrpc":"2.0","id":8,"method":"ping"}"#;
+        let response = dispatch(server, line.to_string()).await.expect("a response");
+        assert!(response.error.is_none());
+    }
+}
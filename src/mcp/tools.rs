@@ -0,0 +1,222 @@
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+use tracing::error;
+use utoipa::{OpenApi, ToSchema};
+
+use crate::{
+    handlers::{blame, commits, diff, metadata::ApiDoc, repositories},
+    models::mcp::{CallToolResult, Content, Tool},
+    utils::git_helpers::GitError,
+};
+
+/// Errors that prevent a tool from being invoked at all
+///
+/// Failures while the tool runs are reported inside the `CallToolResult`
+#[derive(Error, Debug)]
+pub enum ToolError {
+    #[error("Unknown tool: {0}")]
+    UnknownTool(String),
+    #[error("Invalid arguments: {0}")]
+    InvalidArguments(serde_json::Error),
+    #[error("Failed to serialize tool result: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+type ToolHandler = Box<dyn Fn(Value) -> Result<CallToolResult, ToolError> + Send + Sync>;
+
+/// A tool descriptor paired with the function that runs it
+struct RegisteredTool {
+    descriptor: Tool,
+    handler: ToolHandler,
+}
+
+/// Registry of the tools exposed over MCP
+///
+/// Descriptors are derived from the utoipa schemas of each handler's request and
+/// response types, and descriptions from the matching `/tools/{name}` OpenAPI path,
+/// so `tools/list` always reflects the real request structs.
+pub struct ToolRegistry {
+    tools: Vec<RegisteredTool>,
+    openapi: Value,
+}
+
+impl Default for ToolRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ToolRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        ToolRegistry {
+            tools: Vec::new(),
+            openapi: serde_json::to_value(ApiDoc::openapi()).unwrap_or_default(),
+        }
+    }
+
+    /// Create a registry holding every built-in tool
+    pub fn with_default_tools() -> Self {
+        let mut registry = Self::new();
+        registry.register("get_git_blame", blame::blame_response);
+        registry.register("get_reverse_blame", blame::reverse_blame_response);
+        registry.register("get_commit_diff", diff::commit_diff_response);
+        registry.register("summarize_diff", diff::summarize_diff_response);
+        registry.register("get_commits_affecting", commits::commits_affecting_response);
+        registry.register("get_line_history", commits::line_history_response);
+        registry.register("get_symbol_history", commits::symbol_history_response);
+        registry.register("search_commits", commits::search_commits_response);
+        registry.register("pickaxe_search", commits::pickaxe_response);
+        registry.register("get_file_at_commit", commits::file_at_commit_response);
+        registry.register("list_repositories", repositories::list_repositories_response);
+        registry
+    }
+
+    /// Register a handler as a tool
+    ///
+    /// The handler's request and response types must also be listed in `ApiDoc`
+    /// so that nested schema references can be resolved.
+    pub fn register<Req, Res>(&mut self, name: &str, handler: fn(Req) -> Result<Res, GitError>)
+    where
+        Req: ToSchema<'static> + DeserializeOwned + 'static,
+        Res: ToSchema<'static> + Serialize + 'static,
+    {
+        let descriptor = Tool {
+            name: name.to_string(),
+            description: self.description_for(name),
+            input_schema: self.schema_for::<Req>(),
+            output_schema: Some(self.schema_for::<Res>()),
+        };
+
+        self.tools.push(RegisteredTool {
+            descriptor,
+            handler: Box::new(move |arguments| run(arguments, handler)),
+        });
+    }
+
+    /// List the descriptors of every registered tool
+    pub fn list(&self) -> Vec<Tool> {
+        self.tools
+            .iter()
+            .map(|tool| tool.descriptor.clone())
+            .collect()
+    }
+
+    /// Invoke a tool by name with the given JSON arguments
+    pub fn call(&self, name: &str, arguments: Value) -> Result<CallToolResult, ToolError> {
+        let tool = self
+            .tools
+            .iter()
+            .find(|tool| tool.descriptor.name == name)
+            .ok_or_else(|| ToolError::UnknownTool(name.to_string()))?;
+
+        (tool.handler)(arguments)
+    }
+
+    /// Build the tool description from the summary and description of its REST path
+    fn description_for(&self, name: &str) -> String {
+        let operation = self
+            .openapi
+            .pointer(&format!("/paths/~1tools~1{}/post", name));
+
+        ["summary", "description"]
+            .iter()
+            .filter_map(|field| {
+                operation
+                    .and_then(|op| op.get(*field))
+                    .and_then(Value::as_str)
+            })
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join(". ")
+    }
+
+    /// Produce a self-contained JSON schema for a type
+    fn schema_for<T: ToSchema<'static>>(&self) -> Value {
+        let (_, schema) = T::schema();
+        let schema = serde_json::to_value(schema).unwrap_or_default();
+        self.to_json_schema(schema)
+    }
+
+    /// Turn an OpenAPI schema into plain JSON Schema
+    ///
+    /// `#/components/schemas/...` references are inlined and OpenAPI's `nullable`
+    /// flag becomes a `null` entry in the `type` list.
+    fn to_json_schema(&self, value: Value) -> Value {
+        match value {
+            Value::Object(mut map) => {
+                if let Some(name) = map
+                    .get("$ref")
+                    .and_then(Value::as_str)
+                    .and_then(|reference| reference.strip_prefix("#/components/schemas/"))
+                {
+                    if let Some(target) = self
+                        .openapi
+                        .pointer(&format!("/components/schemas/{}", name))
+                    {
+                        return self.to_json_schema(target.clone());
+                    }
+                }
+
+                if map.get("nullable") == Some(&Value::Bool(true)) {
+                    map.remove("nullable");
+                    if let Some(Value::String(ty)) = map.get("type").cloned() {
+                        map.insert("type".to_string(), serde_json::json!([ty, "null"]));
+                    }
+                }
+
+                Value::Object(
+                    map.into_iter()
+                        .map(|(key, value)| (key, self.to_json_schema(value)))
+                        .collect(),
+                )
+            }
+            Value::Array(items) => Value::Array(
+                items
+                    .into_iter()
+                    .map(|item| self.to_json_schema(item))
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+}
+
+/// Deserialize the arguments, run the handler and wrap its output
+fn run<Req, Res>(
+    arguments: Value,
+    handler: fn(Req) -> Result<Res, GitError>,
+) -> Result<CallToolResult, ToolError>
+where
+    Req: DeserializeOwned,
+    Res: Serialize,
+{
+    let request: Req = serde_json::from_value(arguments).map_err(ToolError::InvalidArguments)?;
+
+    let result = match handler(request) {
+        Ok(response) => {
+            let value = serde_json::to_value(&response)?;
+            CallToolResult {
+                content: vec![Content::Text {
+                    text: serde_json::to_string_pretty(&value)?,
+                }],
+                structured_content: Some(value),
+                is_error: false,
+            }
+        }
+        Err(e) => {
+            error!("Tool call failed: {}", e);
+            CallToolResult {
+                content: vec![Content::Text {
+                    text: e.to_string(),
+                }],
+                structured_content: None,
+                is_error: true,
+            }
+        }
+    };
+
+    Ok(result)
+}
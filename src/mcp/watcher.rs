@@ -0,0 +1,163 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use notify::{Event, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+use tracing::{debug, error, info, warn};
+
+use super::McpServer;
+use crate::utils::{
+    git_helpers::{self, GitError},
+    repo_registry,
+};
+
+/// Quiet period after the last filesystem event before refs are re-read
+///
+/// A single commit or fetch touches several files (lock files, logs, refs)
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Watch the repository's refs and notify MCP clients when they move
+///
+/// Watches `HEAD`, `packed-refs` and `refs/`. Failing to set up the watcher is
+/// logged and otherwise ignored, since the server works without it.
+pub fn spawn(server: Arc<McpServer>) {
+    for (name, _) in repo_registry::registry().iter() {
+        if let Err(e) = start(Arc::clone(&server), name.to_string()) {
+            warn!("Ref watcher disabled for '{}': {}", name, e);
+        }
+    }
+}
+
+fn start(server: Arc<McpServer>, repo_name: String) -> Result<(), GitError> {
+    let repo = repo_registry::open(Some(&repo_name))?;
+    let (git_dir, common_dir) = git_helpers::get_ref_dirs(&repo)?;
+    // Events carry resolved paths, so compare against resolved directories
+    let git_dir = git_dir.canonicalize().unwrap_or(git_dir);
+    let common_dir = common_dir.canonicalize().unwrap_or(common_dir);
+    let mut snapshot = git_helpers::get_ref_snapshot(&repo)?;
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let ref_dirs = [git_dir.clone(), common_dir.clone()];
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        match event {
+            Ok(event) if event.paths.iter().any(|path| is_ref_path(path, &ref_dirs)) => {
+                let _ = tx.send(());
+            }
+            Ok(_) => {}
+            Err(e) => error!("Ref watcher error: {}", e),
+        }
+    })
+    .map_err(|e| GitError::Other(format!("failed to create watcher: {}", e)))?;
+
+    let watch = |watcher: &mut notify::RecommendedWatcher, path: &Path, mode| {
+        watcher
+            .watch(path, mode)
+            .map_err(|e| GitError::Other(format!("failed to watch {:?}: {}", path, e)))
+    };
+    watch(&mut watcher, &git_dir, RecursiveMode::NonRecursive)?;
+    if common_dir != git_dir {
+        watch(&mut watcher, &common_dir, RecursiveMode::NonRecursive)?;
+    }
+    watch(&mut watcher, &common_dir.join("refs"), RecursiveMode::Recursive)?;
+
+    info!("Watching refs of '{}' in {:?}", repo_name, common_dir);
+
+    tokio::spawn(async move {
+        // Keep the watcher alive for as long as the task runs
+        let _watcher = watcher;
+
+        while rx.recv().await.is_some() {
+            // Wait until events stop arriving
+            while let Ok(Some(())) = tokio::time::timeout(DEBOUNCE, rx.recv()).await {}
+
+            let name = repo_name.clone();
+            let current = match tokio::task::spawn_blocking(move || {
+                git_helpers::get_ref_snapshot(&repo_registry::open(Some(&name))?)
+            })
+            .await
+            {
+                Ok(Ok(current)) => current,
+                Ok(Err(e)) => {
+                    warn!("Failed to read refs: {}", e);
+                    continue;
+                }
+                Err(e) => {
+                    error!("Ref snapshot task failed: {}", e);
+                    continue;
+                }
+            };
+
+            if current == snapshot {
+                debug!("Ref files touched but no ref moved");
+                continue;
+            }
+
+            let head_moved = head(&current) != head(&snapshot);
+            info!(
+                "Refs moved in '{}' (HEAD moved: {}), notifying clients",
+                repo_name, head_moved
+            );
+            server.refs_changed(&repo_name, head_moved);
+            snapshot = current;
+        }
+    });
+
+    Ok(())
+}
+
+/// Whether a changed path can affect where a ref points
+///
+/// Only `HEAD`, `packed-refs` and files under `refs/` directly inside one of `ref_dirs` count.
+fn is_ref_path(path: &Path, ref_dirs: &[PathBuf]) -> bool {
+    let name = path.file_name().and_then(|name| name.to_str()).unwrap_or("");
+    if name.ends_with(".lock") {
+        return false;
+    }
+
+    ref_dirs.iter().any(|dir| match path.strip_prefix(dir) {
+        Ok(relative) => {
+            relative == Path::new("HEAD")
+                || relative == Path::new("packed-refs")
+                || relative.starts_with("refs")
+        }
+        Err(_) => false,
+    })
+}
+
+fn head(snapshot: &BTreeMap<String, String>) -> Option<&String> {
+    snapshot.get("HEAD")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_refs_of_the_git_dirs_count() {
+        let dirs = [
+            PathBuf::from("/work/wt/.git/worktrees/wt"),
+            PathBuf::from("/work/repo/.git"),
+        ];
+        let is_ref = |path: &str| is_ref_path(Path::new(path), &dirs);
+
+        assert!(is_ref("/work/repo/.git/HEAD"));
+        assert!(is_ref("/work/repo/.git/packed-refs"));
+        assert!(is_ref("/work/repo/.git/refs/heads/main"));
+        assert!(is_ref("/work/repo/.git/refs/tags"));
+        assert!(is_ref("/work/wt/.git/worktrees/wt/HEAD"));
+
+        assert!(!is_ref("/work/repo/.git/refs/heads/main.lock"));
+        assert!(!is_ref("/work/repo/.git/HEAD.lock"));
+        assert!(!is_ref("/work/repo/.git/ORIG_HEAD"));
+        assert!(!is_ref("/work/repo/.git/logs/refs/heads/main"));
+        assert!(!is_ref("/work/repo/.git/logs/HEAD"));
+        assert!(!is_ref("/work/repo/.git/objects/ab/cdef"));
+        assert!(!is_ref("/work/repo/src/refs/mod.rs"));
+        assert!(!is_ref("/work/repo/refs/HEAD"));
+        assert!(!is_ref("/elsewhere/.git/refs/heads/main"));
+    }
+}
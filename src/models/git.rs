@@ -42,6 +42,7 @@ pub struct McpMetadata {
 }
 
 /// Error response for API errors
+#[allow(dead_code)]
 #[derive(Serialize, Debug)]
 pub struct ErrorResponse {
     pub error: String,
@@ -1,2 +1,3 @@
+pub mod mcp;
 pub mod tool_schema;
 pub mod git;
@@ -1,11 +1,54 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+use crate::utils::git_helpers::{
+    BlameLineInfo, FileChangeInfo, FileCommitInfo, LineHistoryEntry, PickaxeCommitInfo,
+    SimpleCommit, SymbolHistoryEntry,
+};
+
 /// Request model for git blame operations
-#[derive(Debug, Deserialize, ToSchema)]
+#[derive(Debug, Default, Deserialize, ToSchema)]
 pub struct BlameRequest {
     /// Path to the file to analyze
     pub file: String,
+    /// Revision to blame the file at (SHA, branch, tag, `HEAD~3`, ...; defaults to HEAD)
+    #[serde(default)]
+    pub rev: Option<String>,
+    /// Use the last commit on `rev` (HEAD by default) at or before this time; returned in `sha`
+    #[serde(default)]
+    pub as_of: Option<String>,
+    /// First line to blame (1-indexed, inclusive; defaults to the first line)
+    #[serde(default)]
+    pub start_line: Option<usize>,
+    /// Last line to blame (1-indexed, inclusive; defaults to the last line)
+    #[serde(default)]
+    pub end_line: Option<usize>,
+    /// Shape of the response: one entry per line, or contiguous hunks plus a commit table
+    #[serde(default)]
+    pub format: BlameFormat,
+    /// Ignore whitespace-only changes, so reformatting commits keep the original author
+    #[serde(default)]
+    pub ignore_whitespace: bool,
+    /// Follow lines moved within the file (`git blame -M`)
+    #[serde(default)]
+    pub track_copies_same_file: bool,
+    /// Follow lines moved from other files changed in the same commit (`git blame -C`)
+    #[serde(default)]
+    pub track_copies_same_commit_moves: bool,
+    /// Follow lines copied from any file in any commit (`git blame -C -C -C`)
+    #[serde(default)]
+    pub track_copies_any_commit_copies: bool,
+    /// Extra revisions to look through, attributing their lines to earlier commits
+    #[serde(default)]
+    pub ignore_revs: Vec<String>,
+    /// Don't read ignored revisions from `blame.ignoreRevsFile` / `.git-blame-ignore-revs`
+    #[serde(default)]
+    pub no_ignore_revs_file: bool,
+    /// Name of the repository to query (defaults to the server's default repository)
+    #[serde(default)]
+    pub repo: Option<String>,
 }
 
 /// Response model for git blame operations
@@ -13,8 +56,44 @@ pub struct BlameRequest {
 pub struct BlameResponse {
     /// The file path that was analyzed
     pub file: String,
-    /// Line by line blame information
-    pub lines: Vec<BlameLine>,
+    /// Full SHA of the commit the blame was computed at
+    pub sha: String,
+    /// Line by line blame information for the requested lines (`lines` format)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines: Option<Vec<BlameLine>>,
+    /// Runs of consecutive lines last changed by the same commit (`hunks` format)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hunks: Option<Vec<BlameHunk>>,
+    /// Commits referenced by `hunks`, keyed by SHA (`hunks` format)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commits: Option<BTreeMap<String, CommitInfo>>,
+}
+
+/// Response shapes for git blame operations
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BlameFormat {
+    /// Every line with its full commit information
+    #[default]
+    Lines,
+    /// Contiguous hunks referencing a shared commit table, like `git blame --porcelain`
+    Hunks,
+}
+
+/// A run of consecutive lines last changed by the same commit
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BlameHunk {
+    /// First line of the hunk (1-indexed)
+    pub start_line: usize,
+    /// Number of lines in the hunk
+    pub line_count: usize,
+    /// SHA of the commit that last changed these lines; a key of `commits`
+    pub sha: String,
+    /// SHA of the ignored commit these lines were re-attributed from, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reattributed_from: Option<String>,
+    /// Content of each line in the hunk
+    pub lines: Vec<String>,
 }
 
 /// Information about a single line in a file
@@ -26,19 +105,85 @@ pub struct BlameLine {
     pub content: String,
     /// Commit information for this line
     pub commit: CommitInfo,
+    /// SHA of the ignored commit this line was re-attributed from, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reattributed_from: Option<String>,
+}
+
+/// Request model for reverse blame operations
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReverseBlameRequest {
+    /// Path to the file at `rev`
+    pub file: String,
+    /// Old revision whose lines are followed forward (SHA, branch, tag, `HEAD~3`, ...)
+    pub rev: String,
+    /// Use the last commit on `rev` at or before this time; returned in `sha`
+    #[serde(default)]
+    pub as_of: Option<String>,
+    /// Newer revision to follow the lines up to (defaults to HEAD); `rev` must be its ancestor
+    #[serde(default)]
+    pub head: Option<String>,
+    /// Use the last commit on `head` (HEAD by default) at or before this time; returned in `head_sha`
+    #[serde(default)]
+    pub head_as_of: Option<String>,
+    /// First line of the file at `rev` to follow (1-indexed, inclusive; defaults to the first line)
+    #[serde(default)]
+    pub start_line: Option<usize>,
+    /// Last line of the file at `rev` to follow (1-indexed, inclusive; defaults to the last line)
+    #[serde(default)]
+    pub end_line: Option<usize>,
+    /// Name of the repository to query (defaults to the server's default repository)
+    #[serde(default)]
+    pub repo: Option<String>,
+}
+
+/// Response model for reverse blame operations
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReverseBlameResponse {
+    /// The file path that was analyzed, as of `rev`
+    pub file: String,
+    /// Full SHA `rev` resolved to
+    pub sha: String,
+    /// Full SHA `head` resolved to
+    pub head_sha: String,
+    /// What happened to each requested line of the file at `rev`
+    pub lines: Vec<ReverseBlameLine>,
+    /// Commits referenced by `lines`, keyed by SHA
+    pub commits: BTreeMap<String, CommitInfo>,
+}
+
+/// How long a single line of an old revision survived
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReverseBlameLine {
+    /// Line number in the file at `rev` (1-indexed)
+    pub line_number: usize,
+    /// Content of the line at `rev`
+    pub content: String,
+    /// SHA of the last commit that still contained the line; a key of `commits`
+    pub last_seen: String,
+    /// SHA of the commit that changed or removed the line, unset if it survives to `head`
+    pub removed_in: Option<String>,
 }
 
 /// Request model for commit diff operations
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct CommitDiffRequest {
-    /// Git commit SHA
-    pub sha: String,
+    /// Commit to show, as any revision expression (SHA, branch, tag, `HEAD~3`, ...; defaults
+    /// to HEAD)
+    #[serde(default)]
+    pub sha: Option<String>,
+    /// Use the last commit on `sha` (HEAD by default) at or before this time; returned in `commit`
+    #[serde(default)]
+    pub as_of: Option<String>,
+    /// Name of the repository to query (defaults to the server's default repository)
+    #[serde(default)]
+    pub repo: Option<String>,
 }
 
 /// Response model for commit diff operations
 #[derive(Debug, Serialize, ToSchema)]
 pub struct CommitDiffResponse {
-    /// Commit information
+    /// Commit information, with the revision resolved to its full SHA
     pub commit: CommitInfo,
     /// List of file changes in this commit
     pub changes: Vec<FileChange>,
@@ -47,18 +192,33 @@ pub struct CommitDiffResponse {
 /// Request model for summarizing diffs between commits
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct SummarizeDiffRequest {
-    /// Base commit SHA
-    pub base: String,
-    /// Head commit SHA
-    pub head: String,
+    /// Base revision (SHA, branch, tag, `HEAD~3`, ...); required unless `range` or `base_as_of` is given
+    #[serde(default)]
+    pub base: Option<String>,
+    /// Head revision (SHA, branch, tag, `HEAD~3`, ...); required unless `range` or `head_as_of` is given
+    #[serde(default)]
+    pub head: Option<String>,
+    /// Use the last commit on `base`, or on the base of `range`, at or before this time
+    #[serde(default)]
+    pub base_as_of: Option<String>,
+    /// Use the last commit on `head`, or on the head of `range`, at or before this time
+    #[serde(default)]
+    pub head_as_of: Option<String>,
+    /// Range to compare instead of `base`/`head`: `A..B` diffs A against B, `A...B` diffs the
+    /// merge base of A and B against B (what B adds on top of A); an omitted side means HEAD
+    #[serde(default)]
+    pub range: Option<String>,
+    /// Name of the repository to query (defaults to the server's default repository)
+    #[serde(default)]
+    pub repo: Option<String>,
 }
 
 /// Response model for summarized diffs
 #[derive(Debug, Serialize, ToSchema)]
 pub struct SummarizeDiffResponse {
-    /// Base commit information
+    /// Base commit information, with the revision resolved to its full SHA
     pub base_commit: CommitInfo,
-    /// Head commit information
+    /// Head commit information, with the revision resolved to its full SHA
     pub head_commit: CommitInfo,
     /// Human-readable summary of the changes
     pub summary: String,
@@ -71,9 +231,33 @@ pub struct SummarizeDiffResponse {
 pub struct CommitsAffectingRequest {
     /// Path to the file to analyze
     pub file: String,
-    /// Optional limit on the number of commits to return
+    /// Revision to start walking back from (defaults to HEAD)
+    #[serde(default)]
+    pub start_rev: Option<String>,
+    /// Use the last commit on `start_rev` (HEAD by default) at or before this time
     #[serde(default)]
+    pub as_of: Option<String>,
+    /// Cursor from a previous response, to fetch the page after it; the other
+    /// options must match the request that produced it
+    #[serde(default)]
+    pub cursor: Option<String>,
+    /// Optional limit on the number of commits to return (at least 1, defaults to 50)
+    #[serde(default)]
+    #[schema(minimum = 1)]
     pub limit: Option<usize>,
+    /// Continue through renames, like `git log --follow`
+    #[serde(default)]
+    pub follow: bool,
+    /// Only walk first parents, listing merges that changed the file relative to their first parent
+    #[serde(default)]
+    pub first_parent: bool,
+    /// Walk every side of each merge instead of pruning sides the file is unchanged from,
+    /// like `git log --full-history`
+    #[serde(default)]
+    pub full_history: bool,
+    /// Name of the repository to query (defaults to the server's default repository)
+    #[serde(default)]
+    pub repo: Option<String>,
 }
 
 /// Response model for commits affecting a file
@@ -81,8 +265,287 @@ pub struct CommitsAffectingRequest {
 pub struct CommitsAffectingResponse {
     /// Path to the file that was analyzed
     pub file: String,
+    /// Full SHA of the commit the history starts from, after resolving `start_rev` and `as_of`
+    pub start_sha: String,
     /// List of commits that modified this file
+    pub commits: Vec<FileCommit>,
+    /// Opaque cursor for the next page, absent once the history is exhausted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
+/// Request model for the history of a line range
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct LineHistoryRequest {
+    /// Path to the file, as of `start_rev`
+    pub file: String,
+    /// First line of the range to trace (1-based); required unless `function` is given
+    #[serde(default)]
+    #[schema(minimum = 1)]
+    pub start_line: Option<usize>,
+    /// Last line of the range to trace, inclusive (defaults to the end of the file); only
+    /// valid with `start_line`
+    #[serde(default)]
+    pub end_line: Option<usize>,
+    /// Regex matching the header line of a function to trace instead of a line range,
+    /// like `git log -L :<funcname>`; replaces `start_line` and `end_line`
+    #[serde(default)]
+    pub function: Option<String>,
+    /// Revision to start walking back from (defaults to HEAD)
+    #[serde(default)]
+    pub start_rev: Option<String>,
+    /// Use the last commit on `start_rev` (HEAD by default) at or before this time
+    #[serde(default)]
+    pub as_of: Option<String>,
+    /// Maximum number of commits to return (at least 1, defaults to 50)
+    #[serde(default)]
+    #[schema(minimum = 1)]
+    pub limit: Option<usize>,
+    /// Name of the repository to query (defaults to the server's default repository)
+    #[serde(default)]
+    pub repo: Option<String>,
+}
+
+/// Response model for the history of a line range
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LineHistoryResponse {
+    /// Path to the file that was analyzed
+    pub file: String,
+    /// Full SHA of the commit the history starts from, after resolving `start_rev` and `as_of`
+    pub start_sha: String,
+    /// Commits that changed the range, newest first
+    pub commits: Vec<LineHistoryCommit>,
+}
+
+/// A commit that changed a traced line range
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LineHistoryCommit {
+    /// The commit
+    pub commit: CommitInfo,
+    /// Path of the file in this commit
+    pub path: String,
+    /// First line of the range in this commit
+    pub start_line: usize,
+    /// Last line of the range in this commit
+    pub end_line: usize,
+    /// Diff of the range against the parent, with the whole range as context
+    pub diff: String,
+}
+
+/// Request model for the history of a named symbol
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SymbolHistoryRequest {
+    /// Path to the file defining the symbol, as of `start_rev`
+    pub file: String,
+    /// Name of the function, method, class, struct or other definition, optionally
+    /// qualified by its enclosing type (`Config::parse`, `Server.start`)
+    pub symbol: String,
+    /// Revision to start walking back from (defaults to HEAD)
+    #[serde(default)]
+    pub start_rev: Option<String>,
+    /// Use the last commit on `start_rev` (HEAD by default) at or before this time
+    #[serde(default)]
+    pub as_of: Option<String>,
+    /// Maximum number of commits to return (at least 1, defaults to 50)
+    #[serde(default)]
+    #[schema(minimum = 1)]
+    pub limit: Option<usize>,
+    /// Name of the repository to query (defaults to the server's default repository)
+    #[serde(default)]
+    pub repo: Option<String>,
+}
+
+/// Response model for the history of a named symbol
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SymbolHistoryResponse {
+    /// Path to the file that was analyzed
+    pub file: String,
+    /// Name of the symbol that was traced
+    pub symbol: String,
+    /// Full SHA of the commit the history starts from, after resolving `start_rev` and `as_of`
+    pub start_sha: String,
+    /// Syntax node kind of the definition, e.g. `function_item`
+    pub kind: String,
+    /// Commits that changed the symbol, newest first
+    pub commits: Vec<SymbolHistoryCommit>,
+}
+
+/// A commit that changed a traced symbol
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SymbolHistoryCommit {
+    /// The commit
+    pub commit: CommitInfo,
+    /// Path of the file in this commit
+    pub path: String,
+    /// Whether the commit added, modified or deleted the symbol
+    pub change: ChangeType,
+    /// First line of the symbol in this commit (in the parent for deletions)
+    pub start_line: usize,
+    /// Last line of the symbol in this commit (in the parent for deletions)
+    pub end_line: usize,
+    /// Diff of the symbol's definition against the parent
+    pub diff: String,
+}
+
+/// Request model for searching commits
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SearchCommitsRequest {
+    /// Commits to search: a revision, `A..B` (in B but not A) or `A...B` (in either but
+    /// not both); defaults to HEAD
+    #[serde(default)]
+    pub range: Option<String>,
+    /// Use the last commit on the head of `range` (HEAD by default) at or before this time
+    #[serde(default)]
+    pub as_of: Option<String>,
+    /// Regex matched against the author's name or email
+    #[serde(default)]
+    pub author: Option<String>,
+    /// Regex matched against the committer's name or email
+    #[serde(default)]
+    pub committer: Option<String>,
+    /// Only commits committed at or after this date (RFC 3339 or `YYYY-MM-DD`)
+    #[serde(default)]
+    pub since: Option<String>,
+    /// Only commits committed at or before this date (RFC 3339, or `YYYY-MM-DD` for the whole day)
+    #[serde(default)]
+    pub until: Option<String>,
+    /// Regex matched against the commit message
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Only commits changing a file under one of these paths
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// Whether to include merge commits, exclude them, or return only merges
+    #[serde(default)]
+    pub merges: MergeFilter,
+    /// Maximum number of commits to return (at least 1, defaults to 50)
+    #[serde(default)]
+    #[schema(minimum = 1)]
+    pub limit: Option<usize>,
+    /// Cursor from a previous response, to fetch the page after it; the other
+    /// options must match the request that produced it, and it is rejected once `range` moves
+    #[serde(default)]
+    pub cursor: Option<String>,
+    /// Name of the repository to query (defaults to the server's default repository)
+    #[serde(default)]
+    pub repo: Option<String>,
+}
+
+/// How a commit search treats merge commits
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeFilter {
+    /// Merges and regular commits alike
+    #[default]
+    Include,
+    /// Regular commits only, like `git log --no-merges`
+    Exclude,
+    /// Merge commits only, like `git log --merges`
+    Only,
+}
+
+/// Response model for a commit search
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchCommitsResponse {
+    /// Matching commits, newest first
     pub commits: Vec<CommitInfo>,
+    /// Opaque cursor for the next page, absent once the search is exhausted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
+/// Request model for a pickaxe search
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PickaxeRequest {
+    /// String to look for
+    pub pattern: String,
+    /// Treat `pattern` as a regular expression instead of a literal string
+    #[serde(default)]
+    pub regex: bool,
+    /// Whether a commit must change the number of occurrences (`git log -S`) or merely
+    /// touch a line containing the pattern (`git log -G`)
+    #[serde(default)]
+    pub mode: PickaxeMode,
+    /// Commits to search: a revision, `A..B` or `A...B`; defaults to HEAD
+    #[serde(default)]
+    pub range: Option<String>,
+    /// Use the last commit on the head of `range` (HEAD by default) at or before this time
+    #[serde(default)]
+    pub as_of: Option<String>,
+    /// Only look at changes to files under these paths
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// Maximum number of commits to return (at least 1, defaults to 50)
+    #[serde(default)]
+    #[schema(minimum = 1)]
+    pub limit: Option<usize>,
+    /// Cursor from a previous response, to fetch the page after it; the other
+    /// options must match the request that produced it, and it is rejected once `range` moves
+    #[serde(default)]
+    pub cursor: Option<String>,
+    /// Name of the repository to query (defaults to the server's default repository)
+    #[serde(default)]
+    pub repo: Option<String>,
+}
+
+/// When a pickaxe search considers that a commit changed the pattern
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PickaxeMode {
+    /// The number of occurrences differs before and after the commit, like `git log -S`
+    #[default]
+    Count,
+    /// An added or removed line contains the pattern, like `git log -G`
+    Lines,
+}
+
+/// Response model for a pickaxe search
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PickaxeResponse {
+    /// Commits that changed the pattern, newest first
+    pub commits: Vec<PickaxeCommit>,
+    /// Opaque cursor for the next page, absent once the search is exhausted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
+/// A commit found by a pickaxe search
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PickaxeCommit {
+    /// The matching commit
+    pub commit: CommitInfo,
+    /// Files whose changes matched the pattern
+    pub files: Vec<PickaxeFile>,
+}
+
+/// A file whose changes matched a pickaxe pattern
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PickaxeFile {
+    /// Path of the file
+    pub path: String,
+    /// Matches on added lines (matching lines in `lines` mode)
+    pub added: usize,
+    /// Matches on removed lines (matching lines in `lines` mode)
+    pub removed: usize,
+    /// Hunks of the file's diff that contain a match
+    pub excerpt: String,
+}
+
+/// A commit that modified a file
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FileCommit {
+    /// Commit SHA
+    pub sha: String,
+    /// Commit message
+    pub message: String,
+    /// Author name
+    pub author: String,
+    /// Author email
+    pub email: String,
+    /// Timestamp of the commit (ISO format)
+    pub timestamp: String,
+    /// Path of the file in this commit, which differs from `file` before a followed rename
+    pub path: String,
 }
 
 /// Request model for getting a file at a specific commit
@@ -90,8 +553,15 @@ pub struct CommitsAffectingResponse {
 pub struct FileAtCommitRequest {
     /// Path to the file
     pub file: String,
-    /// Commit SHA
-    pub sha: String,
+    /// Revision to read the file at (SHA, branch, tag, `HEAD~3`, ...; defaults to HEAD)
+    #[serde(default)]
+    pub sha: Option<String>,
+    /// Use the last commit on `sha` (HEAD by default) at or before this time; returned in `commit`
+    #[serde(default)]
+    pub as_of: Option<String>,
+    /// Name of the repository to query (defaults to the server's default repository)
+    #[serde(default)]
+    pub repo: Option<String>,
 }
 
 /// Response model for file at commit
@@ -99,12 +569,46 @@ pub struct FileAtCommitRequest {
 pub struct FileAtCommitResponse {
     /// Path to the file
     pub file: String,
-    /// Commit information
+    /// Commit information, with the revision resolved to its full SHA
     pub commit: CommitInfo,
     /// File content at the specified commit
     pub content: String,
 }
 
+/// Request model for listing the repositories this server can query
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct ListRepositoriesRequest {}
+
+/// Response model for listing repositories
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ListRepositoriesResponse {
+    /// Every repository registered with the server
+    pub repositories: Vec<RepositoryInfo>,
+}
+
+/// Information about a repository the server can query
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RepositoryInfo {
+    /// Name to pass as `repo` in other requests
+    pub name: String,
+    /// Path of the working tree, or of the git directory for bare repositories
+    pub path: String,
+    /// Whether requests without a `repo` use this repository
+    pub is_default: bool,
+    /// Whether the repository has no working tree
+    pub bare: bool,
+    /// Whether this is a linked worktree of another repository
+    pub worktree: bool,
+    /// Commit SHA HEAD points at (unset for empty repositories)
+    pub head: Option<String>,
+    /// Branch checked out at HEAD (unset when HEAD is detached)
+    pub head_ref: Option<String>,
+    /// Default branch of the repository, if one can be determined
+    pub default_branch: Option<String>,
+    /// Commit time of HEAD (ISO format)
+    pub last_commit_time: Option<String>,
+}
+
 /// Information about a Git commit
 #[derive(Debug, Serialize, ToSchema)]
 pub struct CommitInfo {
@@ -144,3 +648,100 @@ pub enum ChangeType {
     Deleted,
     Renamed,
 }
+
+impl From<SimpleCommit> for CommitInfo {
+    fn from(commit: SimpleCommit) -> Self {
+        CommitInfo {
+            sha: commit.sha,
+            message: commit.message,
+            author: commit.author,
+            email: commit.email,
+            timestamp: commit.timestamp,
+        }
+    }
+}
+
+impl From<FileCommitInfo> for FileCommit {
+    fn from(entry: FileCommitInfo) -> Self {
+        FileCommit {
+            sha: entry.commit.sha,
+            message: entry.commit.message,
+            author: entry.commit.author,
+            email: entry.commit.email,
+            timestamp: entry.commit.timestamp,
+            path: entry.path,
+        }
+    }
+}
+
+impl From<LineHistoryEntry> for LineHistoryCommit {
+    fn from(entry: LineHistoryEntry) -> Self {
+        LineHistoryCommit {
+            commit: entry.commit.into(),
+            path: entry.path,
+            start_line: entry.start_line,
+            end_line: entry.end_line,
+            diff: entry.diff,
+        }
+    }
+}
+
+impl From<SymbolHistoryEntry> for SymbolHistoryCommit {
+    fn from(entry: SymbolHistoryEntry) -> Self {
+        SymbolHistoryCommit {
+            commit: entry.commit.into(),
+            path: entry.path,
+            change: entry.change,
+            start_line: entry.start_line,
+            end_line: entry.end_line,
+            diff: entry.diff,
+        }
+    }
+}
+
+impl From<PickaxeCommitInfo> for PickaxeCommit {
+    fn from(info: PickaxeCommitInfo) -> Self {
+        PickaxeCommit {
+            commit: info.commit.into(),
+            files: info
+                .files
+                .into_iter()
+                .map(|file| PickaxeFile {
+                    path: file.path,
+                    added: file.added,
+                    removed: file.removed,
+                    excerpt: file.excerpt,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<FileChangeInfo> for FileChange {
+    fn from(change: FileChangeInfo) -> Self {
+        FileChange {
+            path: change.path,
+            change_type: change.change_type,
+            diff: change.diff,
+            additions: change.additions,
+            deletions: change.deletions,
+        }
+    }
+}
+
+impl From<BlameLineInfo> for BlameLine {
+    fn from(line: BlameLineInfo) -> Self {
+        BlameLine {
+            line_number: line.line_number,
+            content: line.content,
+            commit: CommitInfo {
+                sha: line.commit_sha,
+                message: line.commit_message,
+                author: line.author,
+                email: line.email,
+                timestamp: line.timestamp,
+            },
+            reattributed_from: line.reattributed_from,
+        }
+    }
+}
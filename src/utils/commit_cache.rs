@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use git2::{Oid, Repository};
+use tracing::debug;
+
+use crate::utils::git_helpers::{self, GitError, SimpleCommit};
+
+/// Upper bound on commits kept per repository between requests
+const MAX_SHARED_COMMITS: usize = 100_000;
+
+/// Whether cached commits outlive the request that looked them up
+static SHARED_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Commits shared between requests, keyed by git directory
+static SHARED: OnceLock<Mutex<HashMap<PathBuf, SharedCommits>>> = OnceLock::new();
+
+/// Commits cached for one repository while its HEAD stays put
+struct SharedCommits {
+    head: Oid,
+    commits: HashMap<Oid, SimpleCommit>,
+}
+
+/// Enable or disable keeping looked-up commits between requests
+///
+/// Sharing is on by default; call this once at startup to opt out.
+pub fn set_shared(enabled: bool) {
+    SHARED_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Commit metadata looked up by OID, loading each commit at most once
+///
+/// Blame resolves the same handful of commits for thousands of lines, so every request
+/// keeps its own cache. With sharing enabled, commits are also kept between requests for
+/// the same repository and dropped as soon as its HEAD moves.
+pub struct CommitCache<'r> {
+    repo: &'r Repository,
+    shared_key: Option<(PathBuf, Oid)>,
+    commits: HashMap<Oid, SimpleCommit>,
+    loaded: Vec<Oid>,
+}
+
+impl<'r> CommitCache<'r> {
+    /// Create a cache for lookups in one repository
+    pub fn new(repo: &'r Repository) -> Self {
+        Self::with_sharing(repo, SHARED_ENABLED.load(Ordering::Relaxed))
+    }
+
+    /// Create a cache that uses the shared cache only if `shared` is set
+    fn with_sharing(repo: &'r Repository, shared: bool) -> Self {
+        let shared_key = if shared {
+            repo.head()
+                .ok()
+                .and_then(|head| head.target())
+                .map(|head| (repo.path().to_path_buf(), head))
+        } else {
+            None
+        };
+
+        CommitCache {
+            repo,
+            shared_key,
+            commits: HashMap::new(),
+            loaded: Vec::new(),
+        }
+    }
+
+    /// Get a commit's metadata, loading it on first use
+    pub fn get(&mut self, oid: Oid) -> Result<&SimpleCommit, GitError> {
+        if !self.commits.contains_key(&oid) {
+            let commit = match self.shared(oid) {
+                Some(commit) => commit,
+                None => {
+                    self.loaded.push(oid);
+                    git_helpers::commit_to_simple(&self.repo.find_commit(oid)?)
+                }
+            };
+            self.commits.insert(oid, commit);
+        }
+
+        Ok(&self.commits[&oid])
+    }
+
+    /// Look a commit up in the cache shared between requests
+    fn shared(&self, oid: Oid) -> Option<SimpleCommit> {
+        let (path, head) = self.shared_key.as_ref()?;
+        let shared = SHARED.get()?.lock().unwrap();
+        shared
+            .get(path)
+            .filter(|entry| entry.head == *head)
+            .and_then(|entry| entry.commits.get(&oid))
+            .cloned()
+    }
+}
+
+impl Drop for CommitCache<'_> {
+    /// Hand the commits this request loaded to the shared cache
+    fn drop(&mut self) {
+        let Some((path, head)) = self.shared_key.take() else {
+            return;
+        };
+        if self.loaded.is_empty() {
+            return;
+        }
+
+        let mut shared = SHARED
+            .get_or_init(|| Mutex::new(HashMap::new()))
+            .lock()
+            .unwrap();
+        let entry = shared.entry(path).or_insert_with(|| SharedCommits {
+            head,
+            commits: HashMap::new(),
+        });
+        if entry.head != head {
+            debug!("HEAD moved, dropping {} cached commits", entry.commits.len());
+            entry.head = head;
+            entry.commits.clear();
+        }
+
+        for oid in self.loaded.drain(..) {
+            if entry.commits.len() >= MAX_SHARED_COMMITS {
+                break;
+            }
+            if let Some(commit) = self.commits.remove(&oid) {
+                entry.commits.insert(oid, commit);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+    use tempfile::TempDir;
+
+    /// A repository with one commit on a branch HEAD points to
+    fn repo() -> (TempDir, Repository, Oid) {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let repo = Repository::init(dir.path()).expect("init repository");
+        let first = commit(&repo, "First");
+        (dir, repo, first)
+    }
+
+    /// Commit an empty tree on top of HEAD, moving HEAD
+    fn commit(repo: &Repository, message: &str) -> Oid {
+        let signature = Signature::now("Test", "test@example.com").expect("create signature");
+        let tree = repo
+            .find_tree(repo.treebuilder(None).unwrap().write().unwrap())
+            .unwrap();
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents)
+            .expect("commit")
+    }
+
+    /// Look commits up in a fresh cache, returning the ones it had to load itself
+    fn load(repo: &Repository, shared: bool, oids: &[Oid]) -> Vec<Oid> {
+        let mut cache = CommitCache::with_sharing(repo, shared);
+        for oid in oids {
+            cache.get(*oid).expect("get commit");
+        }
+        cache.loaded.clone()
+    }
+
+    #[test]
+    fn commits_are_shared_until_head_moves() {
+        let (_dir, repo, first) = repo();
+
+        assert_eq!(load(&repo, true, &[first]), vec![first]);
+        assert_eq!(load(&repo, true, &[first]), vec![]);
+
+        let second = commit(&repo, "Second");
+        assert_eq!(load(&repo, true, &[first, second]), vec![first, second]);
+        assert_eq!(load(&repo, true, &[first, second]), vec![]);
+    }
+
+    #[test]
+    fn unshared_caches_only_last_one_request() {
+        let (_dir, repo, first) = repo();
+
+        let mut cache = CommitCache::with_sharing(&repo, false);
+        assert_eq!(cache.get(first).expect("get commit").message, "First");
+        cache.get(first).expect("get commit");
+        assert_eq!(cache.loaded, vec![first]);
+        drop(cache);
+
+        assert_eq!(load(&repo, false, &[first]), vec![first]);
+        // Nothing was handed to the shared cache either
+        assert_eq!(load(&repo, true, &[first]), vec![first]);
+    }
+
+    #[test]
+    fn set_shared_switches_new_caches() {
+        let (_dir, repo, _) = repo();
+
+        set_shared(false);
+        let unshared = CommitCache::new(&repo).shared_key.is_none();
+        set_shared(true);
+        let shared = CommitCache::new(&repo).shared_key.is_some();
+
+        assert!(unshared);
+        assert!(shared);
+    }
+}
@@ -1,11 +1,22 @@
-use chrono::{DateTime, TimeZone, Utc};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, NaiveDate, NaiveTime, TimeZone, Utc};
 use git2::{
-    BlameOptions, Commit, DiffOptions, ObjectType, Repository, Signature,
+    Blame, BlameOptions, BranchType, Commit, DiffFindOptions, DiffOptions, ObjectType, Patch,
+    Repository, Signature,
 };
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{btree_map::Entry, BTreeMap, BinaryHeap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 use tracing::{debug, error};
-use crate::models::tool_schema::ChangeType;
+use crate::models::tool_schema::{ChangeType, MergeFilter, PickaxeMode};
+use crate::utils::commit_cache::CommitCache;
+use crate::utils::symbols::{find_symbol, Language, Symbol};
+
+/// File listing commits blame should look through, as used by GitHub and GitLab
+const IGNORE_REVS_FILE: &str = ".git-blame-ignore-revs";
 
 /// Custom error type for Git operations
 #[derive(Error, Debug)]
@@ -14,8 +25,12 @@ pub enum GitError {
     GitError(#[from] git2::Error),
     #[error("File not found: {0}")]
     FileNotFound(String),
+    #[error("Unknown repository: {0}")]
+    UnknownRepository(String),
     #[error("Invalid commit: {0}")]
     InvalidCommit(String),
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
     #[error("Other error: {0}")]
@@ -23,6 +38,7 @@ pub enum GitError {
 }
 
 /// Simplified commit information
+#[derive(Clone)]
 pub struct SimpleCommit {
     pub sha: String,
     pub message: String,
@@ -40,6 +56,45 @@ pub struct BlameLineInfo {
     pub author: String,
     pub email: String,
     pub timestamp: String,
+    pub reattributed_from: Option<String>,
+    /// Path of the file in `commit_sha`, which differs from the blamed path across renames
+    pub orig_path: String,
+}
+
+/// What part of a file to blame, and as of which revision
+#[derive(Default)]
+pub struct BlameQuery {
+    pub rev: Option<String>,
+    pub start_line: Option<usize>,
+    pub end_line: Option<usize>,
+    pub ignore_whitespace: bool,
+    pub track_copies_same_file: bool,
+    pub track_copies_same_commit_moves: bool,
+    pub track_copies_any_commit_copies: bool,
+    pub ignore_revs: Vec<String>,
+    pub no_ignore_revs_file: bool,
+}
+
+/// Blame of a file at a revision
+pub struct BlameInfo {
+    pub commit_sha: String,
+    pub lines: Vec<BlameLineInfo>,
+}
+
+/// Reverse blame of one line: the last commit containing it and the one that removed it
+pub struct ReverseBlameLineInfo {
+    pub line_number: usize,
+    pub content: String,
+    pub last_seen_sha: String,
+    pub removed_in_sha: Option<String>,
+}
+
+/// Reverse blame of a file between two revisions
+pub struct ReverseBlameInfo {
+    pub commit_sha: String,
+    pub head_sha: String,
+    pub lines: Vec<ReverseBlameLineInfo>,
+    pub commits: Vec<SimpleCommit>,
 }
 
 /// Information about file changes in a diff
@@ -61,6 +116,132 @@ pub struct FileChangeInfo {
     pub deletions: usize,
 }
 
+/// Which commits of a file's history to list
+#[derive(Default)]
+pub struct HistoryQuery {
+    pub start_rev: Option<String>,
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+    pub follow: bool,
+    pub first_parent: bool,
+    pub full_history: bool,
+}
+
+/// A commit in a file's history, with the file's path at that commit
+pub struct FileCommitInfo {
+    pub commit: SimpleCommit,
+    pub path: String,
+}
+
+/// One page of a file's history
+pub struct FileHistory {
+    pub start_sha: String,
+    pub commits: Vec<FileCommitInfo>,
+    pub cursor: Option<String>,
+}
+
+/// Which lines to trace through history, and from where
+#[derive(Default)]
+pub struct LineHistoryQuery {
+    pub start_rev: Option<String>,
+    pub start_line: Option<usize>,
+    pub end_line: Option<usize>,
+    pub function: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// A commit that changed a traced line range
+pub struct LineHistoryEntry {
+    pub commit: SimpleCommit,
+    pub path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub diff: String,
+}
+
+/// The commits that changed a line range, newest first
+pub struct LineHistory {
+    pub start_sha: String,
+    pub entries: Vec<LineHistoryEntry>,
+}
+
+/// Where to start tracing a symbol's history, and how many changes to return
+#[derive(Default)]
+pub struct SymbolHistoryQuery {
+    pub start_rev: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// A commit that changed a traced symbol
+pub struct SymbolHistoryEntry {
+    pub commit: SimpleCommit,
+    pub path: String,
+    pub change: ChangeType,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub diff: String,
+}
+
+/// The changes to a symbol, newest first
+pub struct SymbolHistory {
+    pub start_sha: String,
+    pub kind: String,
+    pub entries: Vec<SymbolHistoryEntry>,
+}
+
+/// Filters for a commit search
+#[derive(Default)]
+pub struct CommitSearchQuery {
+    pub range: Option<String>,
+    pub author: Option<String>,
+    pub committer: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub message: Option<String>,
+    pub paths: Vec<String>,
+    pub merges: MergeFilter,
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+}
+
+/// One page of commit search results
+pub struct CommitSearchResult {
+    pub commits: Vec<SimpleCommit>,
+    pub cursor: Option<String>,
+}
+
+/// What to look for in a pickaxe search, and where
+#[derive(Default)]
+pub struct PickaxeQuery {
+    pub pattern: String,
+    pub regex: bool,
+    pub mode: PickaxeMode,
+    pub range: Option<String>,
+    pub paths: Vec<String>,
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+}
+
+/// A file whose changes matched a pickaxe pattern
+pub struct PickaxeFileInfo {
+    pub path: String,
+    pub added: usize,
+    pub removed: usize,
+    pub excerpt: String,
+}
+
+/// A commit found by a pickaxe search, with the files that matched
+pub struct PickaxeCommitInfo {
+    pub commit: SimpleCommit,
+    pub files: Vec<PickaxeFileInfo>,
+}
+
+/// One page of pickaxe search results
+pub struct PickaxeResult {
+    pub commits: Vec<PickaxeCommitInfo>,
+    pub cursor: Option<String>,
+}
+
 /// Information for a diff summary
 pub struct DiffSummary {
     pub base_commit: SimpleCommit,
@@ -69,27 +250,22 @@ pub struct DiffSummary {
     pub changes: Vec<FileChangeInfo>,
 }
 
+/// Information describing a repository's current state
+pub struct RepositoryDetails {
+    pub bare: bool,
+    pub worktree: bool,
+    pub head: Option<String>,
+    pub head_ref: Option<String>,
+    pub default_branch: Option<String>,
+    pub last_commit_time: Option<String>,
+}
+
 /// Information for a file at a specific commit
 pub struct FileAtCommitInfo {
     pub commit: SimpleCommit,
     pub content: String,
 }
 
-/// Get the repository path, defaulting to the current directory
-fn get_repo_path() -> PathBuf {
-    // In a real application, this might come from configuration
-    // or be passed as a parameter
-    std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
-}
-
-/// Open the git repository
-fn open_repo() -> Result<Repository, GitError> {
-    let repo_path = get_repo_path();
-    debug!("Opening repository at path: {:?}", repo_path);
-    let repo = Repository::open(repo_path)?;
-    Ok(repo)
-}
-
 /// Convert a git signature to author name, email, and timestamp
 fn signature_to_info(sig: &Signature) -> (String, String, String) {
     let name = sig.name().unwrap_or("Unknown").to_string();
@@ -107,8 +283,13 @@ fn signature_to_info(sig: &Signature) -> (String, String, String) {
     (name, email, timestamp)
 }
 
+/// Abbreviate a full commit SHA for display
+pub(crate) fn short_sha(sha: &str) -> &str {
+    sha.get(..7).unwrap_or(sha)
+}
+
 /// Convert a git commit to our simplified commit format
-fn commit_to_simple(commit: &Commit) -> SimpleCommit {
+pub fn commit_to_simple(commit: &Commit) -> SimpleCommit {
     let message = commit.message().unwrap_or("").to_string();
     let sha = commit.id().to_string();
     
@@ -123,150 +304,884 @@ fn commit_to_simple(commit: &Commit) -> SimpleCommit {
     }
 }
 
-/// Get blame information for a file
-pub fn get_file_blame(file_path: &str) -> Result<Vec<BlameLineInfo>, GitError> {
-    let repo = open_repo()?;
-    let path = Path::new(file_path);
+/// Resolve a revision expression to the commit it names
+///
+/// Accepts anything `git rev-parse` does: full or abbreviated SHAs, branches, tags,
+/// `HEAD~3`, `main@{yesterday}`, ... Annotated tags are peeled to their commit.
+pub fn resolve_commit<'r>(repo: &'r Repository, rev: &str) -> Result<Commit<'r>, GitError> {
+    let object = repo
+        .revparse_single(rev)
+        .map_err(|e| GitError::InvalidCommit(format!("{} ({})", rev, e.message())))?;
+    object
+        .peel_to_commit()
+        .map_err(|_| GitError::InvalidCommit(format!("{} does not name a commit", rev)))
+}
+
+/// Find the last commit on a branch made at or before a point in time
+///
+/// Walks the first-parent chain from `branch` (any revision) and returns the first commit
+/// whose committer date is not after `as_of`. `as_of` is an RFC 3339 timestamp or a
+/// `YYYY-MM-DD` date, which stands for the end of that day in UTC.
+pub fn resolve_as_of<'r>(repo: &'r Repository, branch: &str, as_of: &str) -> Result<Commit<'r>, GitError> {
+    let cutoff = parse_date(as_of, true)?;
+    let mut commit = resolve_commit(repo, branch)?;
     
-    if !path.exists() {
-        return Err(GitError::FileNotFound(file_path.to_string()));
+    while commit.committer().when().seconds() > cutoff {
+        commit = commit.parent(0).map_err(|_| {
+            GitError::InvalidCommit(format!("no commit on {} at or before {}", branch, as_of))
+        })?;
+    }
+    Ok(commit)
+}
+
+/// Apply an optional `as_of` date to a revision
+///
+/// Without a date the revision is returned unchanged. With one, it is treated as the branch
+/// to search (HEAD if absent) and replaced by the full SHA `resolve_as_of` selects.
+pub fn revision_as_of(
+    repo: &Repository,
+    rev: Option<&str>,
+    as_of: Option<&str>,
+) -> Result<Option<String>, GitError> {
+    match as_of {
+        Some(as_of) => {
+            let commit = resolve_as_of(repo, rev.unwrap_or("HEAD"), as_of)?;
+            Ok(Some(commit.id().to_string()))
+        }
+        None => Ok(rev.map(str::to_string)),
     }
+}
+
+/// Apply an optional `as_of` date to the head of a revision or `A..B` / `A...B` range
+///
+/// The head (HEAD if absent) is replaced by the full SHA `resolve_as_of` selects on it; the
+/// base of a range is kept as given.
+pub fn range_as_of(
+    repo: &Repository,
+    range: Option<&str>,
+    as_of: Option<&str>,
+) -> Result<Option<String>, GitError> {
+    let Some(as_of) = as_of else {
+        return Ok(range.map(str::to_string));
+    };
+    let range = range.unwrap_or("HEAD");
+    let (base, separator, head) = match range.split_once("...") {
+        Some((base, head)) => (base, "...", head),
+        None => match range.split_once("..") {
+            Some((base, head)) => (base, "..", head),
+            None => ("", "", range),
+        },
+    };
+    let head = if head.is_empty() { "HEAD" } else { head };
+    let head = resolve_as_of(repo, head, as_of)?.id();
+    Ok(Some(format!("{}{}{}", base, separator, head)))
+}
+
+/// Get blame information for a file
+///
+/// Line contents come from the file's blob at the blamed revision, so files that have
+/// since been deleted or moved can still be blamed
+pub fn get_file_blame(
+    repo: &Repository,
+    file_path: &str,
+    query: &BlameQuery,
+) -> Result<BlameInfo, GitError> {
+    let commit = resolve_commit(repo, query.rev.as_deref().unwrap_or("HEAD"))?;
+    let file_content = read_file_at(repo, &commit, file_path)?;
+    let lines: Vec<&str> = file_content.lines().collect();
     
-    let mut blame_opts = BlameOptions::new();
-    let blame = repo.blame_file(path, Some(&mut blame_opts))?;
+    // Restrict blame to the requested lines; git2 line numbers are 1-indexed and inclusive
+    let (start_line, end_line) =
+        line_range(file_path, lines.len(), query.start_line, query.end_line)?;
     
-    let mut result = Vec::new();
-    let file_content = std::fs::read_to_string(path)?;
-    let lines: Vec<&str> = file_content.lines().collect();
+    let mut blame_opts = blame_options(query, commit.id());
+    if !lines.is_empty() {
+        blame_opts.min_line(start_line).max_line(end_line);
+    }
+    let blame = repo.blame_file(Path::new(file_path), Some(&mut blame_opts))?;
     
-    for (i, line) in lines.iter().enumerate() {
+    let ignored = load_ignore_revs(repo, &commit, query)?;
+    let mut parent_blames = HashMap::new();
+    let mut blamed = Vec::new();
+    
+    for (i, line) in lines.iter().enumerate().take(end_line).skip(start_line - 1) {
         let hunk = match blame.get_line(i + 1) {
             Some(hunk) => hunk,
             None => continue,
         };
         
-        let commit_id = hunk.final_commit_id();
-        let commit = repo.find_commit(commit_id)?;
-        let simple_commit = commit_to_simple(&commit);
+        let mut origin = BlameOrigin {
+            commit_id: hunk.final_commit_id(),
+            path: hunk.path().map(Path::to_path_buf).unwrap_or_else(|| file_path.into()),
+            line: hunk.orig_start_line() + (i + 1 - hunk.final_start_line()),
+        };
+        let mut reattributed_from = None;
         
-        result.push(BlameLineInfo {
+        // Look through ignored commits to whoever wrote the line before them
+        if ignored.contains(&origin.commit_id) {
+            let ignored_id = origin.commit_id;
+            if let Some(owner) = reattribute(repo, query, &ignored, &mut parent_blames, &origin)? {
+                reattributed_from = Some(ignored_id.to_string());
+                origin = owner;
+            }
+        }
+        
+        blamed.push(BlamedLine {
             line_number: i + 1,
-            content: line.to_string(),
+            content: line,
+            origin,
+            reattributed_from,
+        });
+    }
+    
+    if query.track_copies_same_file
+        || query.track_copies_same_commit_moves
+        || query.track_copies_any_commit_copies
+    {
+        follow_copies(repo, query, &ignored, &mut parent_blames, &mut blamed)?;
+    }
+    
+    let mut commits = CommitCache::new(repo);
+    let mut result = Vec::new();
+    for BlamedLine {
+        line_number,
+        content,
+        origin,
+        reattributed_from,
+    } in blamed
+    {
+        let simple_commit = commits.get(origin.commit_id)?.clone();
+        
+        result.push(BlameLineInfo {
+            line_number,
+            content: content.to_string(),
             commit_sha: simple_commit.sha,
             commit_message: simple_commit.message,
             author: simple_commit.author,
             email: simple_commit.email,
             timestamp: simple_commit.timestamp,
+            reattributed_from,
+            orig_path: origin.path.to_string_lossy().into_owned(),
         });
     }
     
-    Ok(result)
+    Ok(BlameInfo {
+        commit_sha: commit.id().to_string(),
+        lines: result,
+    })
 }
 
-/// Get the diff for a specific commit
-pub fn get_commit_diff(sha: &str) -> Result<DiffInfo, GitError> {
-    let repo = open_repo()?;
-    
-    // Find the commit by SHA
-    let oid = git2::Oid::from_str(sha)
-        .map_err(|_| GitError::InvalidCommit(sha.to_string()))?;
-    let commit = repo.find_commit(oid)?;
+/// Validate an optional 1-indexed, inclusive line range against a file's length
+///
+/// Defaults to the whole file; an end past the last line is clamped. An empty file
+/// yields the empty range `(1, 0)`.
+fn line_range(
+    file_path: &str,
+    line_count: usize,
+    start_line: Option<usize>,
+    end_line: Option<usize>,
+) -> Result<(usize, usize), GitError> {
+    let start = start_line.unwrap_or(1);
+    let end = end_line.map_or(line_count, |end| end.min(line_count));
+    if start == 0
+        || end_line.is_some_and(|end| end < start)
+        || (start_line.is_some() && start > line_count)
+    {
+        return Err(GitError::InvalidArgument(format!(
+            "invalid line range {}-{} for {} ({} lines)",
+            start,
+            end_line.unwrap_or(line_count),
+            file_path,
+            line_count
+        )));
+    }
+    Ok((start, end))
+}
+
+/// Where a line came from: a commit, the file's path in it and the line's number there
+#[derive(Clone)]
+struct BlameOrigin {
+    commit_id: git2::Oid,
+    path: PathBuf,
+    line: usize,
+}
+
+/// Build blame options for a query, blaming history up to `newest`
+///
+/// libgit2 accepts but ignores the copy-tracking flags, so `follow_copies` handles those.
+fn blame_options(query: &BlameQuery, newest: git2::Oid) -> BlameOptions {
+    let mut blame_opts = BlameOptions::new();
+    blame_opts
+        .newest_commit(newest)
+        .ignore_whitespace(query.ignore_whitespace);
+    blame_opts
+}
+
+/// Alphanumeric characters a block needs to count as moved within a file, as in `git blame -M`
+const MOVE_SCORE: usize = 20;
+
+/// Alphanumeric characters a block needs to count as copied from another file, as in `git blame -C`
+const COPY_SCORE: usize = 40;
+
+/// A line of the blamed file and where it came from
+struct BlamedLine<'a> {
+    line_number: usize,
+    content: &'a str,
+    origin: BlameOrigin,
+    reattributed_from: Option<String>,
+}
+
+/// Trace lines a commit moved or copied back to the commit that wrote them
+///
+/// Each run of adjacent lines credited to one commit is searched for in that commit's first
+/// parent: in the same file, then in the other files the commit changed, then in every file,
+/// as far as the query's copy-tracking flags allow. Each wider level includes the narrower
+/// ones. Matching blocks are re-blamed in the parent, and the search repeats from there
+/// until no run moves any further.
+fn follow_copies<'r>(
+    repo: &'r Repository,
+    query: &BlameQuery,
+    ignored: &HashSet<git2::Oid>,
+    parent_blames: &mut HashMap<(git2::Oid, PathBuf), Blame<'r>>,
+    lines: &mut [BlamedLine],
+) -> Result<(), GitError> {
+    let mut sources = HashMap::new();
+    // Lines whose current origin has already been searched without a match
+    let mut settled = vec![false; lines.len()];
     
-    // Get the commit's parent
+    loop {
+        let mut moved = false;
+        let mut start = 0;
+        while start < lines.len() {
+            let mut end = start + 1;
+            while end < lines.len()
+                && lines[end].origin.commit_id == lines[start].origin.commit_id
+                && lines[end].origin.path == lines[start].origin.path
+                && lines[end].line_number == lines[end - 1].line_number + 1
+            {
+                end += 1;
+            }
+            
+            if settled[start..end].contains(&false) {
+                settled[start..end].fill(true);
+                let run = &mut lines[start..end];
+                if trace_copied_run(repo, query, ignored, parent_blames, &mut sources, run)? {
+                    moved = true;
+                    settled[start..end].fill(false);
+                }
+            }
+            start = end;
+        }
+        
+        if !moved {
+            return Ok(());
+        }
+    }
+}
+
+/// Move the blocks of a run found in its commit's parent to their owners there
+///
+/// Returns whether any line of the run moved.
+fn trace_copied_run<'r>(
+    repo: &'r Repository,
+    query: &BlameQuery,
+    ignored: &HashSet<git2::Oid>,
+    parent_blames: &mut HashMap<(git2::Oid, PathBuf), Blame<'r>>,
+    sources: &mut HashMap<(git2::Oid, PathBuf), Option<Vec<String>>>,
+    run: &mut [BlamedLine],
+) -> Result<bool, GitError> {
+    let commit = repo.find_commit(run[0].origin.commit_id)?;
     let parent = match commit.parent(0) {
         Ok(parent) => parent,
-        Err(_) => {
-            // For initial commits with no parent
-            let empty_tree = repo.find_tree(repo.treebuilder(None)?.write()?)?;
-            let mut diff_opts = DiffOptions::new();
-            let diff = repo.diff_tree_to_tree(Some(&empty_tree), Some(&commit.tree()?), Some(&mut diff_opts))?;
-            
-            let simple_commit = commit_to_simple(&commit);
-            let changes = process_diff(&diff)?;
+        Err(_) => return Ok(false),
+    };
+    let candidates = copy_candidates(repo, query, &commit, &parent, &run[0].origin.path)?;
+    
+    let mut moved = false;
+    let mut k = 0;
+    while k < run.len() {
+        // The longest block starting at this line found in any candidate
+        let mut best: Option<(&Path, usize, usize)> = None;
+        for (path, score) in &candidates {
+            let source = sources
+                .entry((parent.id(), path.clone()))
+                .or_insert_with(|| {
+                    read_file_at(repo, &parent, &path.to_string_lossy())
+                        .ok()
+                        .map(|content| content.lines().map(str::to_string).collect())
+                });
+            let Some(source) = source else {
+                continue;
+            };
             
-            return Ok(DiffInfo {
-                commit_sha: simple_commit.sha,
-                commit_message: simple_commit.message,
-                author: simple_commit.author,
-                email: simple_commit.email,
-                timestamp: simple_commit.timestamp,
-                changes,
-            });
+            for j in 0..source.len() {
+                let len = run[k..]
+                    .iter()
+                    .zip(&source[j..])
+                    .take_while(|(line, source_line)| line.content == source_line.as_str())
+                    .count();
+                let weight: usize = run[k..k + len]
+                    .iter()
+                    .map(|line| line.content.chars().filter(|c| c.is_alphanumeric()).count())
+                    .sum();
+                if weight >= *score && best.is_none_or(|(_, _, best_len)| len > best_len) {
+                    best = Some((path, j, len));
+                }
+            }
+        }
+        
+        let Some((path, j, len)) = best else {
+            k += 1;
+            continue;
+        };
+        
+        let key = (parent.id(), path.to_path_buf());
+        if !parent_blames.contains_key(&key) {
+            let mut blame_opts = blame_options(query, parent.id());
+            let blame = repo.blame_file(path, Some(&mut blame_opts))?;
+            parent_blames.insert(key.clone(), blame);
+        }
+        for offset in 0..len {
+            let parent_line = j + offset + 1;
+            let Some(hunk) = parent_blames[&key].get_line(parent_line) else {
+                continue;
+            };
+            let mut origin = BlameOrigin {
+                commit_id: hunk.final_commit_id(),
+                path: hunk.path().map(Path::to_path_buf).unwrap_or_else(|| path.to_path_buf()),
+                line: hunk.orig_start_line() + (parent_line - hunk.final_start_line()),
+            };
+            if ignored.contains(&origin.commit_id) {
+                if let Some(owner) = reattribute(repo, query, ignored, parent_blames, &origin)? {
+                    origin = owner;
+                }
+            }
+            run[k + offset].origin = origin;
+            moved = true;
+        }
+        k += len;
+    }
+    
+    Ok(moved)
+}
+
+/// Files of `parent` that lines `commit` added to `path` may have come from, with the
+/// score a block must reach in each
+fn copy_candidates(
+    repo: &Repository,
+    query: &BlameQuery,
+    commit: &Commit,
+    parent: &Commit,
+    path: &Path,
+) -> Result<Vec<(PathBuf, usize)>, GitError> {
+    let mut candidates = vec![(path.to_path_buf(), MOVE_SCORE)];
+    let mut add = |candidate: &Path| {
+        if !candidates.iter().any(|(known, _)| known == candidate) {
+            candidates.push((candidate.to_path_buf(), COPY_SCORE));
         }
     };
     
-    // Get the diff between the commit and its parent
-    let parent_tree = parent.tree()?;
-    let commit_tree = commit.tree()?;
-    let mut diff_opts = DiffOptions::new();
-    diff_opts.show_binary(true).context_lines(3);
+    if query.track_copies_same_commit_moves || query.track_copies_any_commit_copies {
+        let parent_tree = parent.tree()?;
+        let diff = repo.diff_tree_to_tree(Some(&parent_tree), Some(&commit.tree()?), None)?;
+        for delta in diff.deltas() {
+            if delta.status() != git2::Delta::Added {
+                if let Some(old_path) = delta.old_file().path() {
+                    add(old_path);
+                }
+            }
+        }
+        
+        if query.track_copies_any_commit_copies {
+            parent_tree.walk(git2::TreeWalkMode::PreOrder, |dir, entry| {
+                if entry.kind() == Some(ObjectType::Blob) {
+                    if let Some(name) = entry.name() {
+                        add(&Path::new(dir).join(name));
+                    }
+                }
+                git2::TreeWalkResult::Ok
+            })?;
+        }
+    }
     
-    let diff = repo.diff_tree_to_tree(Some(&parent_tree), Some(&commit_tree), Some(&mut diff_opts))?;
+    Ok(candidates)
+}
+
+/// Collect the commits blame should look through
+///
+/// Combines the revisions passed in the query with the ignore-revs file, which is the one
+/// named by `blame.ignoreRevsFile` or else `.git-blame-ignore-revs`. Relative paths are read
+/// from the blamed commit first and from the working tree otherwise.
+fn load_ignore_revs(
+    repo: &Repository,
+    commit: &Commit,
+    query: &BlameQuery,
+) -> Result<HashSet<git2::Oid>, GitError> {
+    let mut ignored = HashSet::new();
+    for rev in &query.ignore_revs {
+        ignored.insert(resolve_commit(repo, rev)?.id());
+    }
     
-    let simple_commit = commit_to_simple(&commit);
-    let changes = process_diff(&diff)?;
+    if query.no_ignore_revs_file {
+        return Ok(ignored);
+    }
     
-    Ok(DiffInfo {
-        commit_sha: simple_commit.sha,
-        commit_message: simple_commit.message,
-        author: simple_commit.author,
-        email: simple_commit.email,
-        timestamp: simple_commit.timestamp,
-        changes,
-    })
+    let file_name = repo
+        .config()
+        .and_then(|config| config.get_path("blame.ignoreRevsFile"))
+        .unwrap_or_else(|_| PathBuf::from(IGNORE_REVS_FILE));
+    
+    let contents = if file_name.is_absolute() {
+        std::fs::read_to_string(&file_name).ok()
+    } else {
+        file_name
+            .to_str()
+            .and_then(|name| read_file_at(repo, commit, name).ok())
+            .or_else(|| {
+                repo.workdir()
+                    .and_then(|workdir| std::fs::read_to_string(workdir.join(&file_name)).ok())
+            })
+    };
+    
+    // Same format as git: one revision per line, `#` starts a comment
+    for entry in contents.iter().flat_map(|contents| contents.lines()) {
+        let rev = entry.split('#').next().unwrap_or("").trim();
+        if rev.is_empty() {
+            continue;
+        }
+        match resolve_commit(repo, rev) {
+            Ok(commit) => {
+                ignored.insert(commit.id());
+            }
+            Err(e) => debug!("Skipping ignored revision {}: {}", rev, e),
+        }
+    }
+    
+    Ok(ignored)
 }
 
-/// Process a git diff into our format
-fn process_diff(diff: &git2::Diff) -> Result<Vec<FileChangeInfo>, GitError> {
-    // First, collect file paths and change types
-    let mut changes = Vec::new();
-    let mut file_diffs = std::collections::HashMap::new();
+/// Find who owned a line before the ignored commit that last changed it
+///
+/// Follows the line into the first parent, re-blames it there and repeats while the owner
+/// is ignored too. Returns `None` when the line cannot be traced past an ignored commit,
+/// e.g. because that commit added it; the line then stays with the ignored commit.
+fn reattribute<'r>(
+    repo: &'r Repository,
+    query: &BlameQuery,
+    ignored: &HashSet<git2::Oid>,
+    parent_blames: &mut HashMap<(git2::Oid, PathBuf), Blame<'r>>,
+    origin: &BlameOrigin,
+) -> Result<Option<BlameOrigin>, GitError> {
+    let mut origin = origin.clone();
+    while ignored.contains(&origin.commit_id) {
+        let commit = repo.find_commit(origin.commit_id)?;
+        let parent = match commit.parent(0) {
+            Ok(parent) => parent,
+            Err(_) => return Ok(None),
+        };
+        let parent_line = match line_in_parent(repo, &commit, &parent, &origin.path, origin.line)? {
+            Some(line) => line,
+            None => return Ok(None),
+        };
+        
+        let key = (parent.id(), origin.path.clone());
+        if !parent_blames.contains_key(&key) {
+            let mut blame_opts = blame_options(query, parent.id());
+            let blame = repo.blame_file(&origin.path, Some(&mut blame_opts))?;
+            parent_blames.insert(key.clone(), blame);
+        }
+        let hunk = match parent_blames[&key].get_line(parent_line) {
+            Some(hunk) => hunk,
+            None => return Ok(None),
+        };
+        
+        origin = BlameOrigin {
+            commit_id: hunk.final_commit_id(),
+            path: hunk.path().map(Path::to_path_buf).unwrap_or(origin.path),
+            line: hunk.orig_start_line() + (parent_line - hunk.final_start_line()),
+        };
+    }
     
-    // First pass: collect file paths and change types
-    diff.foreach(
-        &mut |delta, _| {
-            let path = delta
-                .new_file()
-                .path()
-                .or_else(|| delta.old_file().path())
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or_else(|| "unknown".to_string());
-            
-            let change_type = match delta.status() {
-                git2::Delta::Added => ChangeType::Added,
-                git2::Delta::Deleted => ChangeType::Deleted,
-                git2::Delta::Renamed => ChangeType::Renamed,
-                _ => ChangeType::Modified,
-            };
-            
-            changes.push(FileChangeInfo {
-                path: path.clone(),
-                change_type,
-                diff: Some(String::new()), // Initialize with empty string instead of None
-                additions: 0,
-                deletions: 0,
-            });
-            
-            // Initialize an entry in our hashmap for this file
-            file_diffs.insert(path, (changes.len() - 1, String::new()));
-            
-            true
-        },
-        None,
-        None,
-        None
+    Ok(Some(origin))
+}
+
+/// Map a line of a file in `commit` to the corresponding line in `parent`
+///
+/// Lines inside a changed region map to the same offset in the region it replaced.
+/// Returns `None` for lines the commit added outright.
+fn line_in_parent(
+    repo: &Repository,
+    commit: &Commit,
+    parent: &Commit,
+    path: &Path,
+    line: usize,
+) -> Result<Option<usize>, GitError> {
+    let mut diff_opts = DiffOptions::new();
+    diff_opts.pathspec(path).context_lines(0);
+    let diff = repo.diff_tree_to_tree(
+        Some(&parent.tree()?),
+        Some(&commit.tree()?),
+        Some(&mut diff_opts),
     )?;
     
-    // Second pass: collect line changes
-    diff.foreach(
-        &mut |_, _| { true }, // File callback (required)
-        None,
-        None,
-        Some(&mut |delta, hunk, line| {
-            let path = delta
-                .new_file()
-                .path()
-                .or_else(|| delta.old_file().path())
+    if diff.deltas().any(|delta| delta.status() == git2::Delta::Added) {
+        return Ok(None);
+    }
+    let hunks = match Patch::from_diff(&diff, 0)? {
+        Some(patch) => line_hunks(&patch)?,
+        None => Vec::new(),
+    };
+    
+    // Pure insertions and deletions report the line *before* the change as their start
+    let mut shift: isize = 0;
+    for hunk in hunks {
+        let new_first = if hunk.new_lines == 0 { hunk.new_start + 1 } else { hunk.new_start };
+        if line < new_first {
+            break;
+        }
+        if line < hunk.new_start + hunk.new_lines {
+            if hunk.old_lines == 0 {
+                return Ok(None);
+            }
+            return Ok(Some(hunk.old_start + (line - hunk.new_start).min(hunk.old_lines - 1)));
+        }
+        shift = hunk.old_next() as isize - hunk.new_next() as isize;
+    }
+    
+    Ok(Some((line as isize + shift) as usize))
+}
+
+/// Line ranges replaced by one hunk of a zero-context diff
+struct LineHunk {
+    old_start: usize,
+    old_lines: usize,
+    new_start: usize,
+    new_lines: usize,
+}
+
+impl LineHunk {
+    /// First old line after the hunk; an empty range starts *after* `old_start`
+    fn old_next(&self) -> usize {
+        if self.old_lines == 0 { self.old_start + 1 } else { self.old_start + self.old_lines }
+    }
+    
+    /// First new line after the hunk; an empty range starts *after* `new_start`
+    fn new_next(&self) -> usize {
+        if self.new_lines == 0 { self.new_start + 1 } else { self.new_start + self.new_lines }
+    }
+}
+
+/// Collect the line ranges of each hunk in a patch
+fn line_hunks(patch: &Patch) -> Result<Vec<LineHunk>, GitError> {
+    let mut hunks = Vec::with_capacity(patch.num_hunks());
+    for index in 0..patch.num_hunks() {
+        let (hunk, _) = patch.hunk(index)?;
+        hunks.push(LineHunk {
+            old_start: hunk.old_start() as usize,
+            old_lines: hunk.old_lines() as usize,
+            new_start: hunk.new_start() as usize,
+            new_lines: hunk.new_lines() as usize,
+        });
+    }
+    Ok(hunks)
+}
+
+/// Get reverse blame for a file: how long each line of an old revision survived
+///
+/// Walks from `rev` towards `head` (HEAD by default), following the file through renames.
+/// For every line it reports the last commit that still contained it and the commit that
+/// changed or removed it; lines still present at `head` have no removing commit.
+pub fn get_file_reverse_blame(
+    repo: &Repository,
+    file_path: &str,
+    rev: &str,
+    head: Option<&str>,
+    start_line: Option<usize>,
+    end_line: Option<usize>,
+) -> Result<ReverseBlameInfo, GitError> {
+    let head = head.unwrap_or("HEAD");
+    let from = resolve_commit(repo, rev)?;
+    let to = resolve_commit(repo, head)?;
+    let file_content = read_file_at(repo, &from, file_path)?;
+    let lines: Vec<&str> = file_content.lines().collect();
+    let (start_line, end_line) = line_range(file_path, lines.len(), start_line, end_line)?;
+    
+    let chain = ancestry_path(repo, &from, &to)?
+        .ok_or_else(|| GitError::InvalidArgument(format!("{} is not an ancestor of {}", rev, head)))?;
+    
+    // Position of each tracked line in the current commit, until it is changed
+    let mut positions: Vec<Option<usize>> = (start_line..=end_line).map(Some).collect();
+    let mut fates: Vec<Option<(git2::Oid, git2::Oid)>> = vec![None; positions.len()];
+    let mut remaining = positions.len();
+    let mut path = PathBuf::from(file_path);
+    
+    for pair in chain.windows(2) {
+        if remaining == 0 {
+            break;
+        }
+        let (prev, next) = (&pair[0], &pair[1]);
+        let prev_tree = prev.tree()?;
+        let next_tree = next.tree()?;
+        
+        let prev_blob = prev_tree.get_path(&path).map(|entry| entry.id()).ok();
+        let next_blob = next_tree.get_path(&path).map(|entry| entry.id()).ok();
+        if prev_blob == next_blob {
+            continue;
+        }
+        
+        let hunks = match file_hunks(repo, &prev_tree, &next_tree, &path, next_blob.is_none())? {
+            Some((new_path, hunks)) => {
+                path = new_path;
+                hunks
+            }
+            None => {
+                // The file was deleted, taking every remaining line with it
+                for (position, fate) in positions.iter_mut().zip(fates.iter_mut()) {
+                    if position.take().is_some() {
+                        *fate = Some((prev.id(), next.id()));
+                    }
+                }
+                break;
+            }
+        };
+        
+        for (position, fate) in positions.iter_mut().zip(fates.iter_mut()) {
+            let Some(line) = *position else { continue };
+            match line_in_child(&hunks, line) {
+                Some(moved) => *position = Some(moved),
+                None => {
+                    *position = None;
+                    *fate = Some((prev.id(), next.id()));
+                    remaining -= 1;
+                }
+            }
+        }
+    }
+    
+    let mut commits = CommitCache::new(repo);
+    let mut referenced = BTreeMap::new();
+    let mut result = Vec::with_capacity(fates.len());
+    for (offset, fate) in fates.into_iter().enumerate() {
+        let (last_seen, removed_in) = match fate {
+            Some((last_seen, removed_in)) => (last_seen, Some(removed_in)),
+            None => (to.id(), None),
+        };
+        for oid in std::iter::once(last_seen).chain(removed_in) {
+            if let Entry::Vacant(entry) = referenced.entry(oid) {
+                entry.insert(commits.get(oid)?.clone());
+            }
+        }
+        
+        result.push(ReverseBlameLineInfo {
+            line_number: start_line + offset,
+            content: lines[start_line + offset - 1].to_string(),
+            last_seen_sha: last_seen.to_string(),
+            removed_in_sha: removed_in.map(|oid| oid.to_string()),
+        });
+    }
+    
+    Ok(ReverseBlameInfo {
+        commit_sha: from.id().to_string(),
+        head_sha: to.id().to_string(),
+        lines: result,
+        commits: referenced.into_values().collect(),
+    })
+}
+
+/// Commits leading from `from` to `to`, oldest first, or `None` if `from` is not an ancestor
+///
+/// Prefers first parents, stepping into a merged branch only when `from` lies on it
+fn ancestry_path<'r>(
+    repo: &'r Repository,
+    from: &Commit<'r>,
+    to: &Commit<'r>,
+) -> Result<Option<Vec<Commit<'r>>>, GitError> {
+    // One walk over `from..to`, oldest first, marks every commit descending from `from`
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+    revwalk.push(to.id())?;
+    revwalk.hide(from.id())?;
+    let mut on_path = HashSet::from([from.id()]);
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        if commit.parent_ids().any(|parent| on_path.contains(&parent)) {
+            on_path.insert(commit.id());
+        }
+    }
+    if !on_path.contains(&to.id()) {
+        return Ok(None);
+    }
+    
+    let mut chain = vec![to.clone()];
+    let mut current = to.clone();
+    while current.id() != from.id() {
+        current = match current.parents().find(|parent| on_path.contains(&parent.id())) {
+            Some(parent) => parent,
+            None => return Ok(None),
+        };
+        chain.push(current.clone());
+    }
+    
+    chain.reverse();
+    Ok(Some(chain))
+}
+
+/// Diff one file between two trees, following it if it was renamed
+///
+/// Returns the file's path in `new_tree` and the changed line ranges, or `None` if the
+/// file was deleted
+fn file_hunks(
+    repo: &Repository,
+    old_tree: &git2::Tree,
+    new_tree: &git2::Tree,
+    path: &Path,
+    missing: bool,
+) -> Result<Option<(PathBuf, Vec<LineHunk>)>, GitError> {
+    let mut diff_opts = DiffOptions::new();
+    diff_opts.context_lines(0);
+    if !missing {
+        diff_opts.pathspec(path);
+    }
+    let mut diff = repo.diff_tree_to_tree(Some(old_tree), Some(new_tree), Some(&mut diff_opts))?;
+    if missing {
+        diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+    }
+    
+    for (index, delta) in diff.deltas().enumerate() {
+        if delta.old_file().path() != Some(path) {
+            continue;
+        }
+        let new_path = match (delta.status(), delta.new_file().path()) {
+            (git2::Delta::Deleted, _) | (_, None) => return Ok(None),
+            (_, Some(new_path)) => new_path.to_path_buf(),
+        };
+        let hunks = match Patch::from_diff(&diff, index)? {
+            Some(patch) => line_hunks(&patch)?,
+            None => Vec::new(),
+        };
+        return Ok(Some((new_path, hunks)));
+    }
+    
+    Ok(if missing { None } else { Some((path.to_path_buf(), Vec::new())) })
+}
+
+/// Map a line to its position after a change, or `None` if the change replaced it
+fn line_in_child(hunks: &[LineHunk], line: usize) -> Option<usize> {
+    let mut shift: isize = 0;
+    for hunk in hunks {
+        let old_first = if hunk.old_lines == 0 { hunk.old_start + 1 } else { hunk.old_start };
+        if line < old_first {
+            break;
+        }
+        if line < hunk.old_start + hunk.old_lines {
+            return None;
+        }
+        shift = hunk.new_next() as isize - hunk.old_next() as isize;
+    }
+    Some((line as isize + shift) as usize)
+}
+
+/// Get the diff for a specific commit
+pub fn get_commit_diff(repo: &Repository, sha: &str) -> Result<DiffInfo, GitError> {
+    
+    // Find the commit the revision names
+    let commit = resolve_commit(repo, sha)?;
+    
+    // Get the commit's parent
+    let parent = match commit.parent(0) {
+        Ok(parent) => parent,
+        Err(_) => {
+            // For initial commits with no parent
+            let empty_tree = repo.find_tree(repo.treebuilder(None)?.write()?)?;
+            let mut diff_opts = DiffOptions::new();
+            let diff = repo.diff_tree_to_tree(Some(&empty_tree), Some(&commit.tree()?), Some(&mut diff_opts))?;
+            
+            let simple_commit = commit_to_simple(&commit);
+            let changes = process_diff(&diff)?;
+            
+            return Ok(DiffInfo {
+                commit_sha: simple_commit.sha,
+                commit_message: simple_commit.message,
+                author: simple_commit.author,
+                email: simple_commit.email,
+                timestamp: simple_commit.timestamp,
+                changes,
+            });
+        }
+    };
+    
+    // Get the diff between the commit and its parent
+    let parent_tree = parent.tree()?;
+    let commit_tree = commit.tree()?;
+    let mut diff_opts = DiffOptions::new();
+    diff_opts.show_binary(true).context_lines(3);
+    
+    let diff = repo.diff_tree_to_tree(Some(&parent_tree), Some(&commit_tree), Some(&mut diff_opts))?;
+    
+    let simple_commit = commit_to_simple(&commit);
+    let changes = process_diff(&diff)?;
+    
+    Ok(DiffInfo {
+        commit_sha: simple_commit.sha,
+        commit_message: simple_commit.message,
+        author: simple_commit.author,
+        email: simple_commit.email,
+        timestamp: simple_commit.timestamp,
+        changes,
+    })
+}
+
+/// Process a git diff into our format
+fn process_diff(diff: &git2::Diff) -> Result<Vec<FileChangeInfo>, GitError> {
+    // First, collect file paths and change types
+    let mut changes = Vec::new();
+    let mut file_diffs = std::collections::HashMap::new();
+    
+    // First pass: collect file paths and change types
+    diff.foreach(
+        &mut |delta, _| {
+            let path = delta
+                .new_file()
+                .path()
+                .or_else(|| delta.old_file().path())
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|| "unknown".to_string());
+            
+            let change_type = match delta.status() {
+                git2::Delta::Added => ChangeType::Added,
+                git2::Delta::Deleted => ChangeType::Deleted,
+                git2::Delta::Renamed => ChangeType::Renamed,
+                _ => ChangeType::Modified,
+            };
+            
+            changes.push(FileChangeInfo {
+                path: path.clone(),
+                change_type,
+                diff: Some(String::new()), // Initialize with empty string instead of None
+                additions: 0,
+                deletions: 0,
+            });
+            
+            // Initialize an entry in our hashmap for this file
+            file_diffs.insert(path, (changes.len() - 1, String::new()));
+            
+            true
+        },
+        None,
+        None,
+        None
+    )?;
+    
+    // Second pass: collect line changes
+    diff.foreach(
+        &mut |_, _| { true }, // File callback (required)
+        None,
+        None,
+        Some(&mut |delta, hunk, line| {
+            let path = delta
+                .new_file()
+                .path()
+                .or_else(|| delta.old_file().path())
                 .map(|p| p.to_string_lossy().to_string())
                 .unwrap_or_else(|| "unknown".to_string());
             
@@ -316,17 +1231,65 @@ fn process_diff(diff: &git2::Diff) -> Result<Vec<FileChangeInfo>, GitError> {
 }
 
 /// Generate a summary of changes between two commits
-pub fn summarize_diff(base_sha: &str, head_sha: &str) -> Result<DiffSummary, GitError> {
-    let repo = open_repo()?;
+pub fn summarize_diff(repo: &Repository, base_sha: &str, head_sha: &str) -> Result<DiffSummary, GitError> {
     
     // Find the base and head commits
-    let base_oid = git2::Oid::from_str(base_sha)
-        .map_err(|_| GitError::InvalidCommit(base_sha.to_string()))?;
-    let head_oid = git2::Oid::from_str(head_sha)
-        .map_err(|_| GitError::InvalidCommit(head_sha.to_string()))?;
+    let base_commit = resolve_commit(repo, base_sha)?;
+    let head_commit = resolve_commit(repo, head_sha)?;
+    
+    summarize_commits(repo, &base_commit, &head_commit, false)
+}
+
+/// Generate a summary of changes for a `git diff`-style range
+///
+/// `A..B` compares A with B; `A...B` compares the merge base of A and B with B, i.e. what
+/// B adds on top of A. An omitted side defaults to HEAD. `base_as_of` and `head_as_of` move
+/// A and B back to their last commit at or before that time, before any merge base is taken.
+pub fn summarize_range(
+    repo: &Repository,
+    range: &str,
+    base_as_of: Option<&str>,
+    head_as_of: Option<&str>,
+) -> Result<DiffSummary, GitError> {
+    let (base_rev, head_rev, merge_base) = match range.split_once("...") {
+        Some((base, head)) => (base, head, true),
+        None => match range.split_once("..") {
+            Some((base, head)) => (base, head, false),
+            None => {
+                return Err(GitError::InvalidArgument(format!(
+                    "'{}' is not a range; expected A..B or A...B",
+                    range
+                )))
+            }
+        },
+    };
+    let base_rev = if base_rev.is_empty() { "HEAD" } else { base_rev };
+    let head_rev = if head_rev.is_empty() { "HEAD" } else { head_rev };
+    
+    let resolve = |rev, as_of| match as_of {
+        Some(as_of) => resolve_as_of(repo, rev, as_of),
+        None => resolve_commit(repo, rev),
+    };
+    let head_commit = resolve(head_rev, head_as_of)?;
+    let mut base_commit = resolve(base_rev, base_as_of)?;
+    
+    if merge_base {
+        let oid = repo.merge_base(base_commit.id(), head_commit.id()).map_err(|_| {
+            GitError::InvalidCommit(format!("{} and {} have no common ancestor", base_rev, head_rev))
+        })?;
+        base_commit = repo.find_commit(oid)?;
+    }
     
-    let base_commit = repo.find_commit(base_oid)?;
-    let head_commit = repo.find_commit(head_oid)?;
+    summarize_commits(repo, &base_commit, &head_commit, merge_base)
+}
+
+/// Diff two resolved commits and describe the result
+fn summarize_commits(
+    repo: &Repository,
+    base_commit: &Commit,
+    head_commit: &Commit,
+    merge_base: bool,
+) -> Result<DiffSummary, GitError> {
     
     // Get the trees for diffing
     let base_tree = base_commit.tree()?;
@@ -341,14 +1304,15 @@ pub fn summarize_diff(base_sha: &str, head_sha: &str) -> Result<DiffSummary, Git
     
     // In a real application, this would call an LLM API to generate a summary
     // For now, we'll create a simple summary manually
-    let simple_base_commit = commit_to_simple(&base_commit);
-    let simple_head_commit = commit_to_simple(&head_commit);
+    let simple_base_commit = commit_to_simple(base_commit);
+    let simple_head_commit = commit_to_simple(head_commit);
     
     let stats = diff.stats()?;
     let summary = format!(
-        "Changes between {} and {}: {} files changed, {} insertions(+), {} deletions(-)",
-        &base_sha[0..7],
-        &head_sha[0..7],
+        "Changes between {}{} and {}: {} files changed, {} insertions(+), {} deletions(-)",
+        if merge_base { "merge base " } else { "" },
+        short_sha(&simple_base_commit.sha),
+        short_sha(&simple_head_commit.sha),
         stats.files_changed(),
         stats.insertions(),
         stats.deletions()
@@ -363,98 +1327,2192 @@ pub fn summarize_diff(base_sha: &str, head_sha: &str) -> Result<DiffSummary, Git
 }
 
 /// Get a list of commits that modified a file
+///
+/// Follows git's history simplification: a merge is listed only when the file differs from
+/// every parent, and when it matches one parent the walk continues down that parent alone.
+/// `full_history` walks every parent and lists merges that differ from any of them, while
+/// `first_parent` only ever looks at first parents. With `follow`, rename detection runs on
+/// the commit that introduced the path, and the walk continues under the file's previous
+/// name, like `git log --follow`
+///
+/// The walk starts at `start_rev`, or HEAD, and stops after `limit` matches. The returned
+/// cursor records where it stopped so the next page can pick up from there.
 pub fn get_commits_affecting_file(
+    repo: &Repository,
     file_path: &str,
-    limit: Option<usize>,
-) -> Result<Vec<SimpleCommit>, GitError> {
-    let repo = open_repo()?;
-    
-    // Create a revwalk to iterate through commits
-    let mut revwalk = repo.revwalk()?;
-    revwalk.push_head()?;
-    revwalk.set_sorting(git2::Sort::TIME)?;
+    query: &HistoryQuery,
+) -> Result<FileHistory, GitError> {
     
-    let limit = limit.unwrap_or(50); // Default limit to avoid excessive results
+    let limit = page_limit(query.limit)?;
     let mut result = Vec::new();
+    let mut walk = match &query.cursor {
+        Some(cursor) => HistoryWalk::resume(repo, cursor)?,
+        None => {
+            let start = resolve_commit(repo, query.start_rev.as_deref().unwrap_or("HEAD"))?;
+            HistoryWalk::new(&start, PathBuf::from(file_path))
+        }
+    };
     
-    // Find commits that modified this file
-    for oid in revwalk {
-        if result.len() >= limit {
+    while result.len() < limit {
+        let Some((oid, path)) = walk.pop() else {
             break;
-        }
-        
-        let oid = oid?;
+        };
         let commit = repo.find_commit(oid)?;
+        let tree = commit.tree()?;
+        let entry = path_entry(&tree, &path);
         
-        // Skip merge commits for simplicity
-        if commit.parent_count() > 1 {
-            continue;
-        }
-        
-        let parent = if commit.parent_count() > 0 {
-            Some(commit.parent(0)?)
+        let parents: Vec<Commit> = if query.first_parent {
+            commit.parents().take(1).collect()
         } else {
-            None
+            commit.parents().collect()
         };
+        let parent_trees = parents
+            .iter()
+            .map(Commit::tree)
+            .collect::<Result<Vec<_>, _>>()?;
         
-        let commit_tree = commit.tree()?;
-        let parent_tree = match &parent {
-            Some(parent) => Some(parent.tree()?),
-            None => None,
-        };
+        // A parent is TREESAME when the file is identical in it, or absent from both
+        let treesame: Vec<bool> = parent_trees
+            .iter()
+            .map(|parent_tree| path_entry(parent_tree, &path) == entry)
+            .collect();
         
-        // Compare the trees to see if this file changed
-        let mut diff_opts = DiffOptions::new();
-        diff_opts.pathspec(file_path);
+        let changed = if parents.is_empty() {
+            // The root commit introduced the file if it contains it
+            entry.is_some()
+        } else if query.full_history {
+            treesame.contains(&false)
+        } else {
+            !treesame.contains(&true)
+        };
+        if changed {
+            result.push(FileCommitInfo {
+                commit: commit_to_simple(&commit),
+                path: path.to_string_lossy().to_string(),
+            });
+        }
         
-        let diff = match parent_tree {
-            Some(parent_tree) => repo.diff_tree_to_tree(Some(&parent_tree), Some(&commit_tree), Some(&mut diff_opts))?,
-            None => {
-                // For the initial commit
-                let empty_tree = repo.find_tree(repo.treebuilder(None)?.write()?)?;
-                repo.diff_tree_to_tree(Some(&empty_tree), Some(&commit_tree), Some(&mut diff_opts))?
+        // The first parent the file is unchanged from explains its content, so the
+        // other sides of the merge are pruned
+        if !query.full_history {
+            if let Some(index) = treesame.iter().position(|same| *same) {
+                walk.push(&parents[index], path);
+                continue;
             }
-        };
+        }
         
-        // If the diff has deltas, this commit modified the file
-        if diff.deltas().count() > 0 {
-            result.push(commit_to_simple(&commit));
+        for (parent, parent_tree) in parents.iter().zip(&parent_trees) {
+            let mut parent_path = path.clone();
+            
+            // Older commits know the file under the name it was renamed from
+            if query.follow && entry.is_some() && path_entry(parent_tree, &path).is_none() {
+                if let Some(old_path) = renamed_from(repo, parent_tree, &tree, &path)? {
+                    parent_path = old_path;
+                }
+            }
+            walk.push(parent, parent_path);
         }
     }
     
-    Ok(result)
+    Ok(FileHistory {
+        start_sha: walk.start.to_string(),
+        cursor: if result.len() < limit { None } else { walk.cursor() },
+        commits: result,
+    })
 }
 
-/// Get a file as it existed at a specific commit
-pub fn get_file_at_commit(file_path: &str, sha: &str) -> Result<FileAtCommitInfo, GitError> {
-    let repo = open_repo()?;
-    
-    // Find the commit
-    let oid = git2::Oid::from_str(sha)
-        .map_err(|_| GitError::InvalidCommit(sha.to_string()))?;
-    let commit = repo.find_commit(oid)?;
+/// Number of results per page, defaulting to 50 to avoid excessive results
+///
+/// An empty page would hand back a cursor to the same position, so 0 is rejected.
+fn page_limit(limit: Option<usize>) -> Result<usize, GitError> {
+    match limit {
+        Some(0) => Err(GitError::InvalidArgument("limit must be at least 1".to_string())),
+        limit => Ok(limit.unwrap_or(50)),
+    }
+}
+
+/// Commits still to visit in a file history walk, newest first
+struct HistoryWalk {
+    start: git2::Oid,
+    queue: BinaryHeap<(i64, Reverse<usize>, git2::Oid, PathBuf)>,
+    seen: HashMap<git2::Oid, i64>,
+    pushed: usize,
+}
+
+/// Serialized position of a `HistoryWalk`, carried between pages in a cursor
+#[derive(Serialize, Deserialize)]
+struct WalkPosition {
+    /// Commit the walk started from
+    start: String,
+    /// Queued commits and the path the file has in each
+    queue: Vec<(String, String)>,
+    /// Visited commits recent enough to be reached again from the queue
+    seen: Vec<String>,
+}
+
+impl HistoryWalk {
+    /// Start a walk at `start`, tracking the file under `path`
+    fn new(start: &Commit, path: PathBuf) -> Self {
+        let mut walk = HistoryWalk {
+            start: start.id(),
+            queue: BinaryHeap::new(),
+            seen: HashMap::new(),
+            pushed: 0,
+        };
+        walk.push(start, path);
+        walk
+    }
     
-    // Get the tree for this commit
-    let tree = commit.tree()?;
+    /// Resume a walk from a cursor produced by `cursor`
+    fn resume(repo: &Repository, cursor: &str) -> Result<Self, GitError> {
+        let invalid = || GitError::InvalidArgument(format!("invalid cursor: {}", cursor));
+        let bytes = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+        let position: WalkPosition = serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+        
+        let find = |sha: &str| {
+            git2::Oid::from_str(sha)
+                .and_then(|oid| repo.find_commit(oid))
+                .map_err(|_| invalid())
+        };
+        
+        let mut walk = HistoryWalk {
+            start: find(&position.start)?.id(),
+            queue: BinaryHeap::new(),
+            seen: HashMap::new(),
+            pushed: 0,
+        };
+        for sha in &position.seen {
+            let commit = find(sha)?;
+            walk.seen.insert(commit.id(), commit.time().seconds());
+        }
+        for (sha, path) in &position.queue {
+            walk.push(&find(sha)?, PathBuf::from(path));
+        }
+        Ok(walk)
+    }
     
-    // Find the file in the tree
-    let entry = tree.get_path(Path::new(file_path))
-        .map_err(|_| GitError::FileNotFound(format!("{} at commit {}", file_path, sha)))?;
+    /// Encode the walk's position, or `None` once every commit has been visited
+    ///
+    /// Commits are visited newest first, so only visited commits at least as recent as
+    /// the oldest queued one can be reached again and need remembering.
+    fn cursor(&self) -> Option<String> {
+        let oldest = self.queue.iter().map(|(time, ..)| *time).min()?;
+        let queued: HashSet<git2::Oid> = self.queue.iter().map(|(_, _, oid, _)| *oid).collect();
+        
+        let mut queue: Vec<_> = self.queue.iter().collect();
+        queue.sort();
+        let position = WalkPosition {
+            start: self.start.to_string(),
+            queue: queue
+                .into_iter()
+                .rev()
+                .map(|(_, _, oid, path)| (oid.to_string(), path.to_string_lossy().to_string()))
+                .collect(),
+            seen: self
+                .seen
+                .iter()
+                .filter(|(oid, time)| **time >= oldest && !queued.contains(oid))
+                .map(|(oid, _)| oid.to_string())
+                .collect(),
+        };
+        
+        let json = serde_json::to_vec(&position).ok()?;
+        Some(URL_SAFE_NO_PAD.encode(json))
+    }
     
-    // Get the object and read its content
-    let object = repo.find_object(entry.id(), Some(ObjectType::Blob))?;
-    let blob = object.as_blob().ok_or_else(|| GitError::Other("Not a blob".to_string()))?;
+    /// Queue a commit, tracking the file under `path`, unless it was queued before
+    fn push(&mut self, commit: &Commit, path: PathBuf) {
+        let time = commit.time().seconds();
+        if self.seen.insert(commit.id(), time).is_none() {
+            self.queue.push((time, Reverse(self.pushed), commit.id(), path));
+            self.pushed += 1;
+        }
+    }
     
-    let content = match std::str::from_utf8(blob.content()) {
-        Ok(content) => content.to_string(),
-        Err(_) => {
-            error!("File content is not valid UTF-8");
-            return Err(GitError::Other("File content is not valid UTF-8".to_string()));
+    /// Take the most recent queued commit, breaking ties in queueing order
+    fn pop(&mut self) -> Option<(git2::Oid, PathBuf)> {
+        self.queue.pop().map(|(_, _, oid, path)| (oid, path))
+    }
+}
+
+/// The blob and mode a tree holds at a path
+fn path_entry(tree: &git2::Tree, path: &Path) -> Option<(git2::Oid, i32)> {
+    tree.get_path(path)
+        .ok()
+        .map(|entry| (entry.id(), entry.filemode()))
+}
+
+/// Find the path a file was renamed from between two trees, if it was
+fn renamed_from(
+    repo: &Repository,
+    old_tree: &git2::Tree,
+    new_tree: &git2::Tree,
+    path: &Path,
+) -> Result<Option<PathBuf>, GitError> {
+    let mut diff = repo.diff_tree_to_tree(Some(old_tree), Some(new_tree), None)?;
+    diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+    
+    Ok(diff
+        .deltas()
+        .find(|delta| delta.status() == git2::Delta::Renamed && delta.new_file().path() == Some(path))
+        .and_then(|delta| delta.old_file().path().map(Path::to_path_buf)))
+}
+
+/// Trace the history of a range of lines, like `git log -L`
+///
+/// Starting at `start_rev` (HEAD by default), each commit that changed the range is reported
+/// with the range's position in that commit and a diff restricted to it; the range is then
+/// mapped back through the diff into the parent. Renames are followed. At a merge, a parent
+/// the range is unchanged from is followed if there is one; otherwise the merge is reported
+/// and every parent is followed, like `git log -L`. The walk ends where the lines were
+/// introduced.
+pub fn get_line_history(
+    repo: &Repository,
+    file_path: &str,
+    query: &LineHistoryQuery,
+) -> Result<LineHistory, GitError> {
+    let limit = page_limit(query.limit)?;
+    let commit = resolve_commit(repo, query.start_rev.as_deref().unwrap_or("HEAD"))?;
+    let start_sha = commit.id().to_string();
+    let content = read_file_at(repo, &commit, file_path)?;
+    let (start, end) = match (&query.function, query.start_line) {
+        (Some(_), Some(_)) => {
+            return Err(GitError::InvalidArgument(
+                "expected either `function` or `start_line`, not both".to_string(),
+            ))
+        }
+        (Some(_), None) if query.end_line.is_some() => {
+            return Err(GitError::InvalidArgument(
+                "`end_line` cannot be combined with `function`, which traces the whole function"
+                    .to_string(),
+            ))
+        }
+        (Some(function), None) => function_range(&content, function, file_path)?,
+        (None, Some(_)) => line_range(
+            file_path,
+            content.lines().count(),
+            query.start_line,
+            query.end_line,
+        )?,
+        (None, None) => {
+            return Err(GitError::InvalidArgument(
+                "expected either `function` or `start_line`".to_string(),
+            ))
+        }
+    };
+    
+    let mut pending = vec![TracedRange {
+        commit,
+        path: PathBuf::from(file_path),
+        start,
+        end,
+        content,
+    }];
+    let mut seen = HashSet::new();
+    let mut result = Vec::new();
+    
+    // Visit the newest pending range first so results come out in date order
+    while result.len() < limit {
+        let Some(index) = (0..pending.len()).max_by_key(|&i| {
+            (pending[i].commit.time().seconds(), Reverse(i))
+        }) else {
+            break;
+        };
+        let TracedRange { commit, path, start, end, content } = pending.remove(index);
+        if !seen.insert((commit.id(), path.clone(), start, end)) {
+            continue;
+        }
+        
+        let tree = commit.tree()?;
+        let entry = path_entry(&tree, &path);
+        
+        // Skip straight to a parent the file is unchanged from
+        let parents: Vec<Commit> = commit.parents().collect();
+        let mut same_parent = None;
+        for parent in &parents {
+            if path_entry(&parent.tree()?, &path) == entry {
+                same_parent = Some(parent.clone());
+                break;
+            }
+        }
+        if let Some(parent) = same_parent {
+            pending.push(TracedRange { commit: parent, path, start, end, content });
+            continue;
+        }
+        
+        // Map the range into each parent holding the file, under its old name if renamed
+        let mut mapped = Vec::with_capacity(parents.len());
+        for parent in parents {
+            let parent_tree = parent.tree()?;
+            let old_path = if path_entry(&parent_tree, &path).is_some() {
+                Some(path.clone())
+            } else {
+                renamed_from(repo, &parent_tree, &tree, &path)?
+            };
+            let Some(old_path) = old_path else {
+                continue;
+            };
+            let old_content = read_file_at(repo, &parent, &old_path.to_string_lossy())?;
+            let (touched, old_start, old_end) = {
+                let mut hunk_opts = DiffOptions::new();
+                hunk_opts.context_lines(0);
+                let patch = Patch::from_buffers(
+                    old_content.as_bytes(),
+                    None,
+                    content.as_bytes(),
+                    None,
+                    Some(&mut hunk_opts),
+                )?;
+                range_in_parent(&line_hunks(&patch)?, start, end)
+            };
+            mapped.push((touched, TracedRange {
+                commit: parent,
+                path: old_path,
+                start: old_start,
+                end: old_end,
+                content: old_content,
+            }));
+        }
+        
+        // A parent the range passed through untouched explains it, like a TREESAME parent
+        if let Some(position) = mapped.iter().position(|(touched, _)| !touched) {
+            pending.push(mapped.swap_remove(position).1);
+            continue;
+        }
+        
+        let (old_content, old_range) = match mapped.first() {
+            Some((_, old)) => (old.content.as_str(), (old.start, old.end)),
+            None => ("", (1, 0)),
+        };
+        result.push(LineHistoryEntry {
+            commit: commit_to_simple(&commit),
+            path: path.to_string_lossy().to_string(),
+            start_line: start,
+            end_line: end,
+            diff: range_diff(old_content, old_range, &content, (start, end))?,
+        });
+        
+        // Keep tracing every parent the lines already existed in
+        pending.extend(
+            mapped
+                .into_iter()
+                .map(|(_, old)| old)
+                .filter(|old| old.end >= old.start),
+        );
+    }
+    
+    Ok(LineHistory {
+        start_sha,
+        entries: result,
+    })
+}
+
+/// A line range still to be traced, as it appears in one commit
+struct TracedRange<'r> {
+    commit: Commit<'r>,
+    path: PathBuf,
+    start: usize,
+    end: usize,
+    /// The file's content at `commit`
+    content: String,
+}
+
+/// Find the lines of the function whose header matches a regex, like `git log -L :<funcname>`
+///
+/// The range runs from the first matching line up to the next line starting with a letter,
+/// `_` or `$` (git's default function header rule), without trailing blank lines.
+fn function_range(content: &str, function: &str, file_path: &str) -> Result<(usize, usize), GitError> {
+    let pattern = Regex::new(function)
+        .map_err(|e| GitError::InvalidArgument(format!("invalid function regex: {}", e)))?;
+    let lines: Vec<&str> = content.lines().collect();
+    
+    let start = lines
+        .iter()
+        .position(|line| pattern.is_match(line))
+        .ok_or_else(|| {
+            GitError::InvalidArgument(format!("no line in {} matches '{}'", file_path, function))
+        })?;
+    let next = lines[start + 1..]
+        .iter()
+        .position(|line| line.starts_with(|c: char| c.is_alphabetic() || c == '_' || c == '$'))
+        .map_or(lines.len(), |offset| start + 1 + offset);
+    let end = lines[start..next]
+        .iter()
+        .rposition(|line| !line.trim().is_empty())
+        .map_or(start, |offset| start + offset);
+    
+    Ok((start + 1, end + 1))
+}
+
+/// Map a line range back through a zero-context diff into the old file
+///
+/// Returns whether the diff touched the range and the range's old bounds; lines replaced by
+/// a hunk widen the range to the whole old side of the hunk. An old end before the old start
+/// means the range did not exist in the old file.
+fn range_in_parent(hunks: &[LineHunk], start: usize, end: usize) -> (bool, usize, usize) {
+    let touched = hunks.iter().any(|hunk| {
+        if hunk.new_lines == 0 {
+            // A deletion only touches the range when it sits between two of its lines
+            start <= hunk.new_start && hunk.new_start < end
+        } else {
+            hunk.new_start <= end && start < hunk.new_start + hunk.new_lines
+        }
+    });
+    
+    let map = |line: usize, is_end: bool| {
+        let mut shift: isize = 0;
+        for hunk in hunks {
+            let new_first = if hunk.new_lines == 0 { hunk.new_start + 1 } else { hunk.new_start };
+            if line < new_first {
+                break;
+            }
+            if line < hunk.new_start + hunk.new_lines {
+                // Pure insertions report the old line *before* them as their start
+                return match (is_end, hunk.old_lines) {
+                    (false, 0) => hunk.old_start + 1,
+                    (false, _) => hunk.old_start,
+                    (true, 0) => hunk.old_start,
+                    (true, _) => hunk.old_start + hunk.old_lines - 1,
+                };
+            }
+            shift = hunk.old_next() as isize - hunk.new_next() as isize;
+        }
+        (line as isize + shift) as usize
+    };
+    
+    (touched, map(start, false), map(end, true))
+}
+
+/// Diff the old and new versions of a line range, with the range as context
+fn range_diff(
+    old_content: &str,
+    (old_start, old_end): (usize, usize),
+    new_content: &str,
+    (new_start, new_end): (usize, usize),
+) -> Result<String, GitError> {
+    let slice = |content: &str, start: usize, end: usize| -> String {
+        content
+            .split_inclusive('\n')
+            .skip(start.saturating_sub(1))
+            .take((end + 1).saturating_sub(start))
+            .collect()
+    };
+    let old_lines = slice(old_content, old_start, old_end);
+    let new_lines = slice(new_content, new_start, new_end);
+    
+    let mut diff_opts = DiffOptions::new();
+    diff_opts.context_lines(u32::MAX / 2);
+    let patch = Patch::from_buffers(
+        old_lines.as_bytes(),
+        None,
+        new_lines.as_bytes(),
+        None,
+        Some(&mut diff_opts),
+    )?;
+    
+    // Like unified diffs, an empty side is numbered after the line before it
+    let old_count = (old_end + 1).saturating_sub(old_start);
+    let new_count = (new_end + 1).saturating_sub(new_start);
+    let mut diff = format!(
+        "@@ -{},{} +{},{} @@\n",
+        if old_count == 0 { old_start.saturating_sub(1) } else { old_start },
+        old_count,
+        if new_count == 0 { new_start.saturating_sub(1) } else { new_start },
+        new_count
+    );
+    for hunk in 0..patch.num_hunks() {
+        for index in 0..patch.num_lines_in_hunk(hunk)? {
+            let line = patch.line_in_hunk(hunk, index)?;
+            let prefix = match line.origin() {
+                '+' => '+',
+                '-' => '-',
+                ' ' => ' ',
+                _ => continue,
+            };
+            diff.push(prefix);
+            diff.push_str(&String::from_utf8_lossy(line.content()));
+            if !diff.ends_with('\n') {
+                diff.push('\n');
+            }
         }
+    }
+    Ok(diff)
+}
+
+/// Trace the history of a named function, class or other definition in a file
+///
+/// Walks the file's history from `start_rev` (HEAD by default), following renames, and
+/// locates the symbol in each version with tree-sitter. A commit is reported when the
+/// symbol's text differs from the first parent's; moving a definition without editing it
+/// does not count. The walk ends at the commit that introduced the symbol.
+pub fn get_symbol_history(
+    repo: &Repository,
+    file_path: &str,
+    symbol: &str,
+    query: &SymbolHistoryQuery,
+) -> Result<SymbolHistory, GitError> {
+    let limit = page_limit(query.limit)?;
+    let language = Language::from_path(file_path).ok_or_else(|| {
+        GitError::InvalidArgument(format!(
+            "no symbol parser for {}; supported are Rust, TypeScript, JavaScript, Python and Go",
+            file_path
+        ))
+    })?;
+    let start = resolve_commit(repo, query.start_rev.as_deref().unwrap_or("HEAD"))?;
+    let content = read_file_at(repo, &start, file_path)?;
+    let current = find_symbol(language, &content, symbol)?.ok_or_else(|| {
+        GitError::InvalidArgument(format!("no symbol `{}` in {} at {}", symbol, file_path, start.id()))
+    })?;
+    
+    let mut history = HistoryQuery {
+        start_rev: Some(start.id().to_string()),
+        follow: true,
+        ..Default::default()
     };
+    let mut entries = Vec::new();
+    
+    'walk: loop {
+        let page = get_commits_affecting_file(repo, file_path, &history)?;
+        for entry in page.commits {
+            let commit = repo.find_commit(git2::Oid::from_str(&entry.commit.sha)?)?;
+            let new_content = file_content_at(repo, &entry.path, &commit.id().to_string())?;
+            
+            // The parent knows the file under its old name if this commit renamed it
+            let old_content = match commit.parent(0) {
+                Ok(parent) => {
+                    let parent_tree = parent.tree()?;
+                    let path = Path::new(&entry.path);
+                    let old_path = if path_entry(&parent_tree, path).is_some() {
+                        Some(path.to_path_buf())
+                    } else {
+                        renamed_from(repo, &parent_tree, &commit.tree()?, path)?
+                    };
+                    match old_path {
+                        Some(old_path) => {
+                            file_content_at(repo, &old_path.to_string_lossy(), &parent.id().to_string())?
+                        }
+                        None => None,
+                    }
+                }
+                Err(_) => None,
+            };
+            
+            let locate = |content: &Option<String>| -> Result<Option<Symbol>, GitError> {
+                match content {
+                    Some(content) => find_symbol(language, content, symbol),
+                    None => Ok(None),
+                }
+            };
+            let new_symbol = locate(&new_content)?;
+            let old_symbol = locate(&old_content)?;
+            
+            let change = match (&old_symbol, &new_symbol) {
+                (None, Some(_)) => ChangeType::Added,
+                (Some(_), None) => ChangeType::Deleted,
+                (Some(old), Some(new)) if old.text != new.text => ChangeType::Modified,
+                _ => continue,
+            };
+            // Deleted symbols are located in the parent; a missing side diffs as empty
+            let span = |found: &Option<Symbol>| {
+                found.as_ref().map_or((1, 0), |symbol| (symbol.start_line, symbol.end_line))
+            };
+            let (start_line, end_line) = span(if new_symbol.is_some() { &new_symbol } else { &old_symbol });
+            let diff = range_diff(
+                old_content.as_deref().unwrap_or(""),
+                span(&old_symbol),
+                new_content.as_deref().unwrap_or(""),
+                span(&new_symbol),
+            )?;
+            let introduced = matches!(change, ChangeType::Added);
+            
+            entries.push(SymbolHistoryEntry {
+                commit: entry.commit,
+                path: entry.path,
+                change,
+                start_line,
+                end_line,
+                diff,
+            });
+            if introduced || entries.len() >= limit {
+                break 'walk;
+            }
+        }
+        
+        match page.cursor {
+            Some(cursor) => history.cursor = Some(cursor),
+            None => break,
+        }
+    }
+    
+    Ok(SymbolHistory {
+        start_sha: start.id().to_string(),
+        kind: current.kind,
+        entries,
+    })
+}
+
+/// Read a file at a revision, or `None` if the revision does not contain it
+fn file_content_at(repo: &Repository, file_path: &str, rev: &str) -> Result<Option<String>, GitError> {
+    match get_file_at_commit(repo, file_path, rev) {
+        Ok(file) => Ok(Some(file.content)),
+        Err(GitError::FileNotFound(_)) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Search commits in a range, newest first
+///
+/// Every filter that is set must match. Dates are compared against the committer date,
+/// like `git log --since`/`--until`, and the walk stops at the first commit older than
+/// `since`. A commit matches `paths` when it changed one of them relative to every parent.
+pub fn search_commits(
+    repo: &Repository,
+    query: &CommitSearchQuery,
+) -> Result<CommitSearchResult, GitError> {
+    let author = compile_regex("author", query.author.as_deref())?;
+    let committer = compile_regex("committer", query.committer.as_deref())?;
+    let message = compile_regex("message", query.message.as_deref())?;
+    let since = query.since.as_deref().map(|date| parse_date(date, false)).transpose()?;
+    let until = query.until.as_deref().map(|date| parse_date(date, true)).transpose()?;
+    
+    let range = query.range.as_deref().unwrap_or("HEAD");
+    let mut walk = RangeWalk::new(repo, range, query.cursor.as_deref())?;
+    
+    let limit = page_limit(query.limit)?;
+    let mut commits = Vec::new();
+    let mut exhausted = true;
+    
+    while let Some(commit) = walk.next()? {
+        if commits.len() >= limit {
+            exhausted = false;
+            break;
+        }
+        walk.visit(&commit);
+        
+        let time = commit.committer().when().seconds();
+        if since.is_some_and(|since| time < since) {
+            break;
+        }
+        if until.is_some_and(|until| time > until) {
+            continue;
+        }
+        
+        let is_merge = commit.parent_count() > 1;
+        let merges_match = match query.merges {
+            MergeFilter::Include => true,
+            MergeFilter::Exclude => !is_merge,
+            MergeFilter::Only => is_merge,
+        };
+        if !merges_match
+            || !signature_matches(author.as_ref(), &commit.author())
+            || !signature_matches(committer.as_ref(), &commit.committer())
+            || !message
+                .as_ref()
+                .is_none_or(|re| re.is_match(commit.message().unwrap_or("")))
+        {
+            continue;
+        }
+        if !query.paths.is_empty() && !touches_paths(repo, &commit, &query.paths)? {
+            continue;
+        }
+        
+        commits.push(commit_to_simple(&commit));
+    }
+    
+    Ok(CommitSearchResult {
+        commits,
+        cursor: if exhausted { None } else { walk.cursor() },
+    })
+}
+
+/// Find commits that changed how often a pattern occurs, like `git log -S` / `-G`
+///
+/// Each non-merge commit is diffed against its parent, with renames detected, and matched
+/// against the pattern's occurrences on each file's removed and added lines. In `Count` mode a file matches when the two
+/// counts differ, so moving the pattern around does not count; in `Lines` mode any changed
+/// line containing it matches. Hunks containing a match are kept as the excerpt.
+pub fn pickaxe_search(repo: &Repository, query: &PickaxeQuery) -> Result<PickaxeResult, GitError> {
+    if query.pattern.is_empty() {
+        return Err(GitError::InvalidArgument("pattern must not be empty".to_string()));
+    }
+    let source = if query.regex {
+        query.pattern.clone()
+    } else {
+        regex::escape(&query.pattern)
+    };
+    let pattern = Regex::new(&source)
+        .map_err(|e| GitError::InvalidArgument(format!("invalid pattern regex: {}", e)))?;
+    
+    let range = query.range.as_deref().unwrap_or("HEAD");
+    let mut walk = RangeWalk::new(repo, range, query.cursor.as_deref())?;
+    
+    let limit = page_limit(query.limit)?;
+    let mut commits = Vec::new();
+    let mut exhausted = true;
+    
+    while let Some(commit) = walk.next()? {
+        if commits.len() >= limit {
+            exhausted = false;
+            break;
+        }
+        walk.visit(&commit);
+        
+        // Like git, merges are not searched since their changes come from their parents
+        if commit.parent_count() > 1 {
+            continue;
+        }
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+        
+        let mut diff_opts = DiffOptions::new();
+        for path in &query.paths {
+            diff_opts.pathspec(path);
+        }
+        let mut diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), Some(&mut diff_opts))?;
+        // Pair renamed files so moving a file is not a removal plus an addition
+        diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+        
+        let files: Vec<PickaxeFileInfo> = process_diff(&diff)?
+            .into_iter()
+            .filter_map(|change| pickaxe_file(&pattern, &query.mode, change))
+            .collect();
+        if !files.is_empty() {
+            commits.push(PickaxeCommitInfo {
+                commit: commit_to_simple(&commit),
+                files,
+            });
+        }
+    }
+    
+    Ok(PickaxeResult {
+        commits,
+        cursor: if exhausted { None } else { walk.cursor() },
+    })
+}
+
+/// Match one file's diff against a pickaxe pattern
+fn pickaxe_file(pattern: &Regex, mode: &PickaxeMode, change: FileChangeInfo) -> Option<PickaxeFileInfo> {
+    let diff = change.diff?;
+    let mut added = 0;
+    let mut removed = 0;
+    let mut excerpt = String::new();
+    
+    for hunk in split_hunks(&diff) {
+        let mut hunk_matches = false;
+        for line in hunk.lines() {
+            let (count, text) = if let Some(text) = line.strip_prefix('+') {
+                (&mut added, text)
+            } else if let Some(text) = line.strip_prefix('-') {
+                (&mut removed, text)
+            } else {
+                continue;
+            };
+            let matches = match mode {
+                PickaxeMode::Count => pattern.find_iter(text).count(),
+                PickaxeMode::Lines => usize::from(pattern.is_match(text)),
+            };
+            *count += matches;
+            hunk_matches |= matches > 0;
+        }
+        if hunk_matches {
+            excerpt.push_str(hunk);
+        }
+    }
+    
+    let matched = match mode {
+        PickaxeMode::Count => added != removed,
+        PickaxeMode::Lines => added + removed > 0,
+    };
+    matched.then_some(PickaxeFileInfo {
+        path: change.path,
+        added,
+        removed,
+        excerpt,
+    })
+}
+
+/// Split a diff produced by `process_diff` into its hunks, each starting with its `@@` header
+fn split_hunks(diff: &str) -> Vec<&str> {
+    let mut hunks = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+    
+    for line in diff.split_inclusive('\n') {
+        if line.starts_with("@@ ") && offset > start {
+            hunks.push(&diff[start..offset]);
+            start = offset;
+        }
+        offset += line.len();
+    }
+    if offset > start {
+        hunks.push(&diff[start..offset]);
+    }
+    hunks
+}
+
+/// The commits a revision or an `A..B` / `A...B` range starts from and the ones it excludes
+#[derive(Clone, Serialize, Deserialize, PartialEq)]
+struct RangeEnds {
+    tips: Vec<String>,
+    hidden: Vec<String>,
+}
+
+/// Resolve a revision or range to the commits bounding it
+fn resolve_range(repo: &Repository, range: &str) -> Result<RangeEnds, GitError> {
+    let (base_rev, head_rev, symmetric) = match range.split_once("...") {
+        Some((base, head)) => (base, head, true),
+        None => match range.split_once("..") {
+            Some((base, head)) => (base, head, false),
+            None => {
+                return Ok(RangeEnds {
+                    tips: vec![resolve_commit(repo, range)?.id().to_string()],
+                    hidden: Vec::new(),
+                })
+            }
+        },
+    };
+    let base_rev = if base_rev.is_empty() { "HEAD" } else { base_rev };
+    let head_rev = if head_rev.is_empty() { "HEAD" } else { head_rev };
+    
+    let base = resolve_commit(repo, base_rev)?.id();
+    let head = resolve_commit(repo, head_rev)?.id();
+    
+    Ok(if symmetric {
+        RangeEnds {
+            tips: vec![head.to_string(), base.to_string()],
+            hidden: repo.merge_base(base, head).ok().map(|oid| oid.to_string()).into_iter().collect(),
+        }
+    } else {
+        RangeEnds {
+            tips: vec![head.to_string()],
+            hidden: vec![base.to_string()],
+        }
+    })
+}
+
+/// A newest-first walk over a range that can stop after any commit and resume from a cursor
+///
+/// Alongside the revwalk it tracks the walk's frontier: the commits reachable from the
+/// visited ones that have not been visited themselves. A cursor records the range's ends and
+/// the frontier, so the next page pushes the frontier instead of walking the range again.
+struct RangeWalk<'r> {
+    repo: &'r Repository,
+    revwalk: git2::Revwalk<'r>,
+    ends: RangeEnds,
+    hidden: Vec<git2::Oid>,
+    frontier: HashMap<git2::Oid, i64>,
+    visited: HashMap<git2::Oid, i64>,
+}
+
+/// Serialized position of a `RangeWalk`, carried between pages in a cursor
+#[derive(Serialize, Deserialize)]
+struct RangePosition {
+    /// The range's ends when the walk started; the cursor is rejected once they move
+    ends: RangeEnds,
+    /// Commits still to visit, along with everything they reach
+    frontier: Vec<String>,
+    /// Visited commits old enough to be reached again from the frontier
+    visited: Vec<String>,
+}
+
+impl<'r> RangeWalk<'r> {
+    /// Start walking `range`, or resume where `cursor` left off
+    fn new(repo: &'r Repository, range: &str, cursor: Option<&str>) -> Result<Self, GitError> {
+        let ends = resolve_range(repo, range)?;
+        let oid = |sha: &String| git2::Oid::from_str(sha).map_err(GitError::from);
+        let hidden = ends.hidden.iter().map(oid).collect::<Result<Vec<_>, _>>()?;
+        
+        let mut revwalk = repo.revwalk()?;
+        revwalk.set_sorting(git2::Sort::TIME)?;
+        for id in &hidden {
+            revwalk.hide(*id)?;
+        }
+        
+        let mut walk = RangeWalk {
+            repo,
+            revwalk,
+            ends,
+            hidden,
+            frontier: HashMap::new(),
+            visited: HashMap::new(),
+        };
+        let Some(cursor) = cursor else {
+            for sha in walk.ends.tips.clone() {
+                walk.queue(&repo.find_commit(oid(&sha)?)?)?;
+            }
+            return Ok(walk);
+        };
+        
+        let invalid = || GitError::InvalidArgument(format!("invalid cursor: {}", cursor));
+        let bytes = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+        let position: RangePosition = serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+        if position.ends != walk.ends {
+            return Err(GitError::InvalidArgument(format!(
+                "cursor is stale: {} has moved since the first page",
+                range
+            )));
+        }
+        
+        let find = |sha: &String| {
+            git2::Oid::from_str(sha)
+                .and_then(|oid| repo.find_commit(oid))
+                .map_err(|_| invalid())
+        };
+        for sha in &position.visited {
+            let commit = find(sha)?;
+            walk.visited.insert(commit.id(), commit.time().seconds());
+        }
+        for sha in &position.frontier {
+            walk.queue(&find(sha)?)?;
+        }
+        Ok(walk)
+    }
+    
+    /// Add a commit to the frontier
+    fn queue(&mut self, commit: &Commit) -> Result<(), GitError> {
+        if self.frontier.insert(commit.id(), commit.time().seconds()).is_none() {
+            self.revwalk.push(commit.id())?;
+        }
+        Ok(())
+    }
+    
+    /// The next commit to visit, or `None` at the end of the range
+    ///
+    /// The commit stays on the frontier until it is passed to `visit`.
+    fn next(&mut self) -> Result<Option<Commit<'r>>, GitError> {
+        for oid in self.revwalk.by_ref() {
+            let oid = oid?;
+            if !self.visited.contains_key(&oid) {
+                return Ok(Some(self.repo.find_commit(oid)?));
+            }
+        }
+        Ok(None)
+    }
+    
+    /// Move a commit returned by `next` from the frontier to the visited commits
+    fn visit(&mut self, commit: &Commit) {
+        self.frontier.remove(&commit.id());
+        self.visited.insert(commit.id(), commit.time().seconds());
+        for parent in commit.parents() {
+            if !self.visited.contains_key(&parent.id()) {
+                self.frontier.entry(parent.id()).or_insert(parent.time().seconds());
+            }
+        }
+    }
+    
+    /// Encode the walk's position, or `None` when nothing in the range is left to visit
+    ///
+    /// Frontier commits the range excludes are dropped. Commits are visited newest first,
+    /// so a visited commit can only be reached again from the frontier when it is no newer
+    /// than the newest frontier commit; only those are remembered.
+    fn cursor(&self) -> Option<String> {
+        let frontier: Vec<(&git2::Oid, &i64)> = self
+            .frontier
+            .iter()
+            .filter(|(oid, _)| {
+                !self.hidden.iter().any(|hidden| {
+                    hidden == *oid || self.repo.graph_descendant_of(*hidden, **oid).unwrap_or(false)
+                })
+            })
+            .collect();
+        let newest = frontier.iter().map(|(_, time)| **time).max()?;
+        
+        let position = RangePosition {
+            ends: self.ends.clone(),
+            frontier: frontier.iter().map(|(oid, _)| oid.to_string()).collect(),
+            visited: self
+                .visited
+                .iter()
+                .filter(|(_, time)| **time <= newest)
+                .map(|(oid, _)| oid.to_string())
+                .collect(),
+        };
+        
+        let json = serde_json::to_vec(&position).ok()?;
+        Some(URL_SAFE_NO_PAD.encode(json))
+    }
+}
+
+/// Compile an optional regex filter, reporting which one was invalid
+fn compile_regex(name: &str, pattern: Option<&str>) -> Result<Option<Regex>, GitError> {
+    pattern
+        .map(|pattern| {
+            Regex::new(pattern)
+                .map_err(|e| GitError::InvalidArgument(format!("invalid {} regex: {}", name, e)))
+        })
+        .transpose()
+}
+
+/// Parse an RFC 3339 timestamp or a `YYYY-MM-DD` date into seconds since the epoch
+///
+/// A bare date means the start of that day in UTC, or its last second with `end_of_day`.
+fn parse_date(date: &str, end_of_day: bool) -> Result<i64, GitError> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(date) {
+        return Ok(timestamp.timestamp());
+    }
+    
+    let day = NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| {
+        GitError::InvalidArgument(format!(
+            "invalid date '{}'; expected RFC 3339 or YYYY-MM-DD",
+            date
+        ))
+    })?;
+    let time = if end_of_day {
+        NaiveTime::from_hms_opt(23, 59, 59)
+    } else {
+        NaiveTime::from_hms_opt(0, 0, 0)
+    };
+    Ok(day.and_time(time.unwrap_or_default()).and_utc().timestamp())
+}
+
+/// Whether a signature's name or email matches an optional filter
+fn signature_matches(filter: Option<&Regex>, signature: &Signature) -> bool {
+    filter.is_none_or(|re| {
+        re.is_match(signature.name().unwrap_or("")) || re.is_match(signature.email().unwrap_or(""))
+    })
+}
+
+/// Whether a commit changed any of the given paths relative to all of its parents
+fn touches_paths(repo: &Repository, commit: &Commit, paths: &[String]) -> Result<bool, GitError> {
+    let tree = commit.tree()?;
+    let mut diff_opts = DiffOptions::new();
+    for path in paths {
+        diff_opts.pathspec(path);
+    }
+    
+    if commit.parent_count() == 0 {
+        let diff = repo.diff_tree_to_tree(None, Some(&tree), Some(&mut diff_opts))?;
+        return Ok(diff.deltas().len() > 0);
+    }
+    for parent in commit.parents() {
+        let diff = repo.diff_tree_to_tree(Some(&parent.tree()?), Some(&tree), Some(&mut diff_opts))?;
+        if diff.deltas().len() == 0 {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Get a file as it existed at a specific commit
+pub fn get_file_at_commit(repo: &Repository, file_path: &str, sha: &str) -> Result<FileAtCommitInfo, GitError> {
+    
+    // Find the commit
+    let commit = resolve_commit(repo, sha)?;
+    let content = read_file_at(repo, &commit, file_path)?;
     
     Ok(FileAtCommitInfo {
         commit: commit_to_simple(&commit),
         content,
     })
 }
+
+/// Read a text file from a commit's tree
+fn read_file_at(repo: &Repository, commit: &Commit, file_path: &str) -> Result<String, GitError> {
+    
+    // Get the tree for this commit
+    let tree = commit.tree()?;
+    
+    // Find the file in the tree
+    let entry = tree.get_path(Path::new(file_path))
+        .map_err(|_| GitError::FileNotFound(format!("{} at commit {}", file_path, commit.id())))?;
+    
+    // Get the object and read its content
+    let object = repo.find_object(entry.id(), Some(ObjectType::Blob))?;
+    let blob = object.as_blob().ok_or_else(|| GitError::Other("Not a blob".to_string()))?;
+    
+    match std::str::from_utf8(blob.content()) {
+        Ok(content) => Ok(content.to_string()),
+        Err(_) => {
+            error!("File content is not valid UTF-8");
+            Err(GitError::Other("File content is not valid UTF-8".to_string()))
+        }
+    }
+}
+
+/// Get the most recent commits reachable from HEAD
+pub fn get_recent_commits(repo: &Repository, limit: usize) -> Result<Vec<SimpleCommit>, GitError> {
+    
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_head()?;
+    revwalk.set_sorting(git2::Sort::TIME)?;
+    
+    let mut result = Vec::new();
+    for oid in revwalk.take(limit) {
+        let commit = repo.find_commit(oid?)?;
+        result.push(commit_to_simple(&commit));
+    }
+    
+    Ok(result)
+}
+
+/// Get the commit currently checked out at HEAD
+pub fn get_head_commit(repo: &Repository) -> Result<SimpleCommit, GitError> {
+    let commit = repo.head()?.peel_to_commit()?;
+    Ok(commit_to_simple(&commit))
+}
+
+/// Get the commits reachable from `head_sha` but not from `base_sha`, newest first
+pub fn get_commits_in_range(
+    repo: &Repository,
+    base_sha: &str,
+    head_sha: &str,
+    limit: usize,
+) -> Result<Vec<SimpleCommit>, GitError> {
+    
+    let base_oid = resolve_commit(repo, base_sha)?.id();
+    let head_oid = resolve_commit(repo, head_sha)?.id();
+    
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(head_oid)?;
+    revwalk.hide(base_oid)?;
+    revwalk.set_sorting(git2::Sort::TIME)?;
+    
+    let mut result = Vec::new();
+    for oid in revwalk.take(limit) {
+        let commit = repo.find_commit(oid?)?;
+        result.push(commit_to_simple(&commit));
+    }
+    
+    Ok(result)
+}
+
+/// Get the directories holding the repository's HEAD and its shared refs
+///
+/// These differ for linked worktrees, where HEAD lives in the worktree's own git dir
+pub fn get_ref_dirs(repo: &Repository) -> Result<(PathBuf, PathBuf), GitError> {
+    let git_dir = repo.path().to_path_buf();
+    
+    // Linked worktrees point at the shared git dir through a `commondir` file
+    let common_dir = match std::fs::read_to_string(git_dir.join("commondir")) {
+        Ok(relative) => git_dir.join(relative.trim()),
+        Err(_) => git_dir.clone(),
+    };
+    
+    Ok((git_dir, common_dir))
+}
+
+/// Snapshot where HEAD and every reference currently point
+pub fn get_ref_snapshot(repo: &Repository) -> Result<BTreeMap<String, String>, GitError> {
+    let mut snapshot = BTreeMap::new();
+    
+    if let Ok(head) = repo.head() {
+        if let Some(oid) = head.target() {
+            snapshot.insert("HEAD".to_string(), oid.to_string());
+        }
+    }
+    
+    for reference in repo.references()? {
+        let reference = reference?;
+        if let (Some(name), Some(oid)) = (reference.name(), reference.target()) {
+            snapshot.insert(name.to_string(), oid.to_string());
+        }
+    }
+    
+    Ok(snapshot)
+}
+
+/// Describe a repository: where HEAD points, its default branch and when it last changed
+///
+/// Empty repositories and detached HEADs leave the corresponding fields unset
+pub fn get_repository_details(repo: &Repository) -> Result<RepositoryDetails, GitError> {
+    let head = repo.head().ok();
+    let head_commit = head.as_ref().and_then(|head| head.peel_to_commit().ok());
+
+    let head_ref = head
+        .as_ref()
+        .filter(|head| head.is_branch())
+        .and_then(|head| head.shorthand())
+        .map(str::to_string);
+
+    let last_commit_time = head_commit
+        .as_ref()
+        .map(|commit| signature_to_info(&commit.committer()).2);
+
+    Ok(RepositoryDetails {
+        bare: repo.is_bare(),
+        worktree: repo.is_worktree(),
+        head: head_commit.map(|commit| commit.id().to_string()),
+        head_ref,
+        default_branch: get_default_branch(repo),
+        last_commit_time,
+    })
+}
+
+/// Guess a repository's default branch
+///
+/// Prefers the branch `origin/HEAD` points at, then `init.defaultBranch`,
+/// then a local `main` or `master`
+fn get_default_branch(repo: &Repository) -> Option<String> {
+    if let Ok(origin_head) = repo.find_reference("refs/remotes/origin/HEAD") {
+        if let Some(target) = origin_head.symbolic_target() {
+            if let Some(branch) = target.strip_prefix("refs/remotes/origin/") {
+                return Some(branch.to_string());
+            }
+        }
+    }
+
+    if let Ok(branch) = repo
+        .config()
+        .and_then(|config| config.get_string("init.defaultBranch"))
+    {
+        if repo.find_branch(&branch, BranchType::Local).is_ok() {
+            return Some(branch);
+        }
+    }
+
+    ["main", "master"]
+        .iter()
+        .find(|name| repo.find_branch(name, BranchType::Local).is_ok())
+        .map(|name| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use tempfile::TempDir;
+
+    /// A throwaway repository whose commits are built directly from file snapshots
+    struct TestRepo {
+        _dir: TempDir,
+        repo: Repository,
+        clock: Cell<i64>,
+    }
+
+    impl TestRepo {
+        fn new() -> Self {
+            let dir = tempfile::tempdir().expect("create temp dir");
+            let repo = Repository::init(dir.path()).expect("init repository");
+            TestRepo {
+                _dir: dir,
+                repo,
+                clock: Cell::new(1_700_000_000),
+            }
+        }
+
+        /// Commit a complete snapshot of top-level files on top of `parents`
+        ///
+        /// Each commit is an hour after the previous one, and HEAD moves to it.
+        fn commit(
+            &self,
+            parents: &[git2::Oid],
+            files: &[(&str, &str)],
+            message: &str,
+        ) -> git2::Oid {
+            let mut builder = self.repo.treebuilder(None).expect("create tree builder");
+            for (path, content) in files {
+                let blob = self.repo.blob(content.as_bytes()).expect("write blob");
+                builder.insert(path, blob, 0o100644).expect("insert blob");
+            }
+            let tree = self
+                .repo
+                .find_tree(builder.write().expect("write tree"))
+                .expect("find tree");
+
+            let time = self.clock.get() + 3_600;
+            self.clock.set(time);
+            let signature = Signature::new("Test", "test@example.com", &git2::Time::new(time, 0))
+                .expect("create signature");
+            let parents: Vec<Commit> = parents
+                .iter()
+                .map(|id| self.repo.find_commit(*id).expect("find parent"))
+                .collect();
+            let parents: Vec<&Commit> = parents.iter().collect();
+            let id = self
+                .repo
+                .commit(None, &signature, &signature, message, &tree, &parents)
+                .expect("commit");
+            self.repo.set_head_detached(id).expect("move HEAD");
+            id
+        }
+    }
+
+    /// The error `resolve_commit` gives for `rev`
+    fn resolve_error(repo: &TestRepo, rev: &str) -> String {
+        match resolve_commit(&repo.repo, rev) {
+            Ok(commit) => panic!("{} resolved to {}", rev, commit.id()),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn resolves_revision_expressions() {
+        let repo = TestRepo::new();
+        let a = repo.commit(&[], &[("f", "1\n")], "A");
+        let b = repo.commit(&[a], &[("f", "2\n")], "B");
+        let c = repo.commit(&[b], &[("f", "3\n")], "C");
+        let target = repo.repo.find_object(b, None).expect("find B");
+        let signature = Signature::now("Test", "test@example.com").expect("create signature");
+        repo.repo.tag("v1", &target, &signature, "Release", false).expect("tag");
+
+        let resolve = |rev: &str| resolve_commit(&repo.repo, rev).expect(rev).id();
+        assert_eq!(resolve("HEAD"), c);
+        assert_eq!(resolve("HEAD~2"), a);
+        assert_eq!(resolve("HEAD^"), b);
+        assert_eq!(resolve(&c.to_string()[..7]), c);
+        // Annotated tags are peeled, with or without an explicit `^{commit}`
+        assert_eq!(resolve("v1^{commit}"), b);
+        assert_eq!(resolve("v1"), b);
+        assert_eq!(resolve("v1~1"), a);
+    }
+
+    #[test]
+    fn rejects_revisions_that_name_no_commit() {
+        let repo = TestRepo::new();
+        repo.commit(&[], &[("f", "1\n")], "A");
+
+        assert_eq!(
+            resolve_error(&repo, "nope"),
+            "Invalid commit: nope (revspec 'nope' not found)"
+        );
+        assert!(resolve_error(&repo, "HEAD~5").starts_with("Invalid commit: HEAD~5 ("));
+        assert_eq!(
+            resolve_error(&repo, "HEAD^{tree}"),
+            "Invalid commit: HEAD^{tree} does not name a commit"
+        );
+        assert_eq!(
+            resolve_error(&repo, "HEAD:f"),
+            "Invalid commit: HEAD:f does not name a commit"
+        );
+    }
+
+    #[test]
+    fn rejects_ambiguous_short_shas() {
+        let repo = TestRepo::new();
+        repo.commit(&[], &[("f", "1\n")], "A");
+
+        // Write blobs until two share the shortest prefix git accepts
+        let mut seen = HashMap::new();
+        let prefix = (0..)
+            .find_map(|i| {
+                let id = repo.repo.blob(format!("{}\n", i).as_bytes()).expect("write blob");
+                let prefix = id.to_string()[..4].to_string();
+                seen.insert(prefix.clone(), id).map(|_| prefix)
+            })
+            .expect("a shared prefix");
+
+        let error = resolve_error(&repo, &prefix);
+        assert!(error.starts_with(&format!("Invalid commit: {} (", prefix)), "{}", error);
+        assert!(error.contains("ambiguous"), "{}", error);
+    }
+
+    #[test]
+    fn ranges_diff_from_the_merge_base_only_with_three_dots() {
+        let repo = TestRepo::new();
+        let a = repo.commit(&[], &[("f", "1\n")], "A");
+        let main = repo.commit(&[a], &[("f", "1\n"), ("g", "main\n")], "Main");
+        let topic = repo.commit(&[a], &[("f", "2\n")], "Topic");
+        repo.repo.branch("main", &repo.repo.find_commit(main).unwrap(), false).unwrap();
+        repo.repo.branch("topic", &repo.repo.find_commit(topic).unwrap(), false).unwrap();
+
+        let changes = |summary: &DiffSummary| -> Vec<String> {
+            summary
+                .changes
+                .iter()
+                .map(|change| format!("{:?} {}", change.change_type, change.path))
+                .collect()
+        };
+
+        // `...` diffs the topic against where it forked, hiding main's own work
+        let forked = summarize_range(&repo.repo, "main...topic", None, None).expect("summarize");
+        assert_eq!(forked.base_commit.sha, a.to_string());
+        assert_eq!(forked.head_commit.sha, topic.to_string());
+        assert_eq!(changes(&forked), vec!["Modified f"]);
+        let expected = format!("Changes between merge base {} ", short_sha(&a.to_string()));
+        assert!(forked.summary.starts_with(&expected), "{}", forked.summary);
+
+        // `..` diffs the two tips directly
+        let direct = summarize_range(&repo.repo, "main..topic", None, None).expect("summarize");
+        assert_eq!(direct.base_commit.sha, main.to_string());
+        assert_eq!(changes(&direct), vec!["Modified f", "Deleted g"]);
+        let expected = format!("Changes between {} ", short_sha(&main.to_string()));
+        assert!(direct.summary.starts_with(&expected), "{}", direct.summary);
+
+        // An empty side stands for HEAD, which is at the topic
+        let to_head = summarize_range(&repo.repo, "main...", None, None).expect("summarize");
+        assert_eq!(to_head.base_commit.sha, a.to_string());
+        assert_eq!(to_head.head_commit.sha, topic.to_string());
+
+        assert!(matches!(
+            summarize_range(&repo.repo, "main", None, None),
+            Err(GitError::InvalidArgument(_))
+        ));
+    }
+
+    /// Content, owning commit and ignored commit of each blamed line
+    fn blame(
+        repo: &TestRepo,
+        path: &str,
+        query: &BlameQuery,
+    ) -> Vec<(String, String, Option<String>)> {
+        get_file_blame(&repo.repo, path, query)
+            .expect("blame")
+            .lines
+            .into_iter()
+            .map(|line| (line.content, line.commit_sha, line.reattributed_from))
+            .collect()
+    }
+
+    #[test]
+    fn ignored_reformat_is_looked_through() {
+        let repo = TestRepo::new();
+        let a = repo.commit(&[], &[("f", "a\nb\nc\nd\n")], "A");
+        let b = repo.commit(&[a], &[("f", "a\nB\nc\nd\n")], "B");
+        let fmt = repo.commit(&[b], &[("f", "a;\nB;\nc\nd\nnew\n")], "Reformat");
+
+        let query = BlameQuery {
+            ignore_revs: vec![fmt.to_string()],
+            ..Default::default()
+        };
+        let (a, b, fmt) = (a.to_string(), b.to_string(), fmt.to_string());
+        assert_eq!(
+            blame(&repo, "f", &query),
+            vec![
+                ("a;".to_string(), a.clone(), Some(fmt.clone())),
+                ("B;".to_string(), b, Some(fmt.clone())),
+                ("c".to_string(), a.clone(), None),
+                ("d".to_string(), a, None),
+                // Added by the ignored commit, so there is no one else to credit
+                ("new".to_string(), fmt, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn ignore_revs_file_is_honored_unless_disabled() {
+        let repo = TestRepo::new();
+        let a = repo.commit(&[], &[("f", "one\ntwo\n")], "A");
+        let fmt = repo.commit(&[a], &[("f", "one;\ntwo;\n")], "Reformat");
+        let ignore_file = format!("# formatting\n{}\n", fmt);
+        repo.commit(&[fmt], &[("f", "one;\ntwo;\n"), (IGNORE_REVS_FILE, &ignore_file)], "Ignore");
+
+        let owners = |query: &BlameQuery| -> Vec<String> {
+            blame(&repo, "f", query).into_iter().map(|(_, sha, _)| sha).collect()
+        };
+        assert_eq!(owners(&BlameQuery::default()), vec![a.to_string(); 2]);
+        assert_eq!(
+            owners(&BlameQuery {
+                no_ignore_revs_file: true,
+                ..Default::default()
+            }),
+            vec![fmt.to_string(); 2]
+        );
+    }
+
+    #[test]
+    fn whitespace_changes_can_be_ignored() {
+        let repo = TestRepo::new();
+        let a = repo.commit(&[], &[("f", "a\n  b\nc\n")], "A");
+        let b = repo.commit(&[a], &[("f", "a\n    b\nc\n")], "Reindent");
+
+        let owner = |ignore_whitespace| {
+            let query = BlameQuery {
+                ignore_whitespace,
+                ..Default::default()
+            };
+            blame(&repo, "f", &query).remove(1).1
+        };
+        assert_eq!(owner(false), b.to_string());
+        assert_eq!(owner(true), a.to_string());
+    }
+
+    const MOVED: &str = "fn moved() {\n    let total = compute_the_total(items);\n    report(total);\n}\n";
+    const STAYED: &str = "fn stayed() {\n    keep_doing_the_same_thing();\n}\n";
+
+    /// Owner of each line of a blamed file, named by commit message
+    fn owners(repo: &TestRepo, path: &str, query: &BlameQuery) -> Vec<String> {
+        blame(repo, path, query)
+            .into_iter()
+            .map(|(_, sha, _)| {
+                let commit = repo.repo.find_commit(sha.parse().unwrap()).unwrap();
+                commit.summary().unwrap().to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn moves_within_a_file_are_followed() {
+        let repo = TestRepo::new();
+        let a = repo.commit(&[], &[("f", &format!("{}{}", MOVED, STAYED))], "A");
+        repo.commit(&[a], &[("f", &format!("{}{}", STAYED, MOVED))], "Move");
+
+        let same_file = BlameQuery {
+            track_copies_same_file: true,
+            ..Default::default()
+        };
+        // The diff keeps one function in place and credits the move with the other
+        assert_eq!(
+            owners(&repo, "f", &BlameQuery::default()),
+            [vec!["Move"; 3], vec!["A"; 4]].concat()
+        );
+        assert_eq!(owners(&repo, "f", &same_file), vec!["A"; 7]);
+    }
+
+    #[test]
+    fn copies_from_other_files_are_followed_as_far_as_asked() {
+        let repo = TestRepo::new();
+        let a = repo.commit(&[], &[("f", &format!("{}{}", MOVED, STAYED))], "A");
+        let b = repo.commit(&[a], &[("f", STAYED), ("g", MOVED)], "Move to g");
+        repo.commit(&[b], &[("f", STAYED), ("g", MOVED), ("h", MOVED)], "Copy to h");
+
+        let query = |same_file, same_commit_moves, any_commit_copies| BlameQuery {
+            track_copies_same_file: same_file,
+            track_copies_same_commit_moves: same_commit_moves,
+            track_copies_any_commit_copies: any_commit_copies,
+            ..Default::default()
+        };
+
+        // The move is found once files changed in the same commit are searched
+        assert_eq!(owners(&repo, "g", &query(true, false, false)), vec!["Move to g"; 4]);
+        assert_eq!(owners(&repo, "g", &query(false, true, false)), vec!["A"; 4]);
+
+        // `g` was untouched by the copy, so only searching every file finds it, and the
+        // search continues through the earlier move
+        assert_eq!(owners(&repo, "h", &query(false, true, false)), vec!["Copy to h"; 4]);
+        assert_eq!(owners(&repo, "h", &query(false, false, true)), vec!["A"; 4]);
+    }
+
+    #[test]
+    fn short_copied_lines_stay_with_their_commit() {
+        let repo = TestRepo::new();
+        let a = repo.commit(&[], &[("f", "}\nx = 1\n")], "A");
+        repo.commit(&[a], &[("f", "}\nx = 1\n"), ("g", "}\nx = 1\n")], "Copy");
+
+        let query = BlameQuery {
+            track_copies_any_commit_copies: true,
+            ..Default::default()
+        };
+        assert_eq!(owners(&repo, "g", &query), vec!["Copy"; 2]);
+    }
+
+    #[test]
+    fn blame_is_limited_to_the_line_range() {
+        let repo = TestRepo::new();
+        let a = repo.commit(&[], &[("f", "1\n2\n3\n4\n")], "A");
+        let b = repo.commit(&[a], &[("f", "1\ntwo\n3\n4\n")], "B");
+
+        let lines = |start_line, end_line| -> Vec<(usize, String)> {
+            let query = BlameQuery {
+                start_line,
+                end_line,
+                ..Default::default()
+            };
+            get_file_blame(&repo.repo, "f", &query)
+                .expect("blame")
+                .lines
+                .into_iter()
+                .map(|line| (line.line_number, line.commit_sha))
+                .collect()
+        };
+        let (a, b) = (a.to_string(), b.to_string());
+
+        assert_eq!(lines(Some(2), Some(3)), vec![(2, b.clone()), (3, a.clone())]);
+        assert_eq!(lines(Some(4), None), vec![(4, a.clone())]);
+        assert_eq!(lines(None, Some(1)), vec![(1, a.clone())]);
+        // An end past the last line is clamped
+        assert_eq!(lines(Some(3), Some(99)), vec![(3, a.clone()), (4, a)]);
+        assert_eq!(lines(None, None).len(), 4);
+    }
+
+    #[test]
+    fn line_ranges_are_validated() {
+        assert_eq!(line_range("f", 4, None, None).unwrap(), (1, 4));
+        assert_eq!(line_range("f", 4, Some(4), Some(4)).unwrap(), (4, 4));
+        assert_eq!(line_range("f", 0, None, None).unwrap(), (1, 0));
+
+        let error = |start_line, end_line| match line_range("f", 4, start_line, end_line) {
+            Err(GitError::InvalidArgument(message)) => message,
+            other => panic!("expected an invalid range, got {:?}", other.ok()),
+        };
+        assert_eq!(error(Some(0), None), "invalid line range 0-4 for f (4 lines)");
+        assert_eq!(error(Some(3), Some(2)), "invalid line range 3-2 for f (4 lines)");
+        assert_eq!(error(Some(5), None), "invalid line range 5-4 for f (4 lines)");
+        assert_eq!(error(None, Some(0)), "invalid line range 1-0 for f (4 lines)");
+    }
+
+    /// Last commit containing and commit removing each reverse-blamed line
+    fn reverse_blame(
+        repo: &TestRepo,
+        path: &str,
+        rev: git2::Oid,
+        head: git2::Oid,
+    ) -> Vec<(String, Option<String>)> {
+        get_file_reverse_blame(
+            &repo.repo,
+            path,
+            &rev.to_string(),
+            Some(&head.to_string()),
+            None,
+            None,
+        )
+        .expect("reverse blame")
+        .lines
+        .into_iter()
+        .map(|line| (line.last_seen_sha, line.removed_in_sha))
+        .collect()
+    }
+
+    #[test]
+    fn reverse_blame_follows_lines_across_renames() {
+        let repo = TestRepo::new();
+        let first = repo.commit(&[], &[("f", "a\nb\nc\nd\n")], "Add f");
+        let edit = repo.commit(&[first], &[("f", "a\nB\nc\nd\n")], "Edit b");
+        let rename = repo.commit(&[edit], &[("g", "a\nB\nc\nd\n")], "Rename f to g");
+        let trim = repo.commit(&[rename], &[("g", "a\nB\nc\n")], "Drop d");
+
+        let sha = |id: git2::Oid| id.to_string();
+        assert_eq!(
+            reverse_blame(&repo, "f", first, trim),
+            vec![
+                (sha(trim), None),
+                (sha(first), Some(sha(edit))),
+                (sha(trim), None),
+                (sha(rename), Some(sha(trim))),
+            ]
+        );
+
+        let info = get_file_reverse_blame(&repo.repo, "f", &sha(first), None, Some(2), Some(2))
+            .expect("reverse blame");
+        let referenced: HashSet<String> = info.commits.into_iter().map(|c| c.sha).collect();
+        assert_eq!(referenced, HashSet::from([sha(first), sha(edit)]));
+    }
+
+    #[test]
+    fn reverse_blame_walks_into_merged_branches() {
+        let repo = TestRepo::new();
+        let base = repo.commit(&[], &[("f", "x\ny\n")], "Base");
+        let main = repo.commit(&[base], &[("f", "x\ny\n"), ("other", "1\n")], "Main");
+        let side = repo.commit(&[base], &[("f", "x\nY\n")], "Side");
+        let merge = repo.commit(&[main, side], &[("f", "x\nY\n"), ("other", "1\n")], "Merge");
+        let head = repo.commit(&[merge], &[("f", "X\nY\n"), ("other", "1\n")], "Edit x");
+
+        let sha = |id: git2::Oid| id.to_string();
+        assert_eq!(
+            reverse_blame(&repo, "f", side, head),
+            vec![(sha(merge), Some(sha(head))), (sha(head), None)]
+        );
+
+        let unrelated =
+            get_file_reverse_blame(&repo.repo, "f", &sha(main), Some(&sha(side)), None, None);
+        assert!(matches!(unrelated, Err(GitError::InvalidArgument(_))));
+    }
+
+    /// A history with a rename, a merge TREESAME to its side branch and an evil merge
+    ///
+    /// Returns the repository and its commits, oldest first.
+    fn branchy_history() -> (TestRepo, Vec<git2::Oid>) {
+        let repo = TestRepo::new();
+        let c1 = repo.commit(&[], &[("f", "1\n2\n3\n4\n5\n"), ("x", "a\n")], "Add f");
+        let c2 = repo.commit(&[c1], &[("f", "1\n2\n3\n4\n5\n"), ("x", "b\n")], "Touch x");
+        let c3 = repo.commit(&[c2], &[("f", "one\n2\n3\n4\n5\n"), ("x", "b\n")], "Edit f");
+        let side = repo.commit(&[c3], &[("f", "one\n2\n3\n4\nfive\n"), ("x", "b\n")], "Side");
+        let main = repo.commit(&[c3], &[("f", "one\n2\n3\n4\n5\n"), ("x", "c\n")], "Main");
+        let merge = repo.commit(
+            &[main, side],
+            &[("f", "one\n2\n3\n4\nfive\n"), ("x", "c\n")],
+            "Merge side",
+        );
+        let rename = repo.commit(
+            &[merge],
+            &[("g", "one\n2\n3\n4\nfive\n"), ("x", "c\n")],
+            "Rename f to g",
+        );
+        let left =
+            repo.commit(&[rename], &[("g", "one\ntwo\n3\n4\nfive\n"), ("x", "c\n")], "Left");
+        let right =
+            repo.commit(&[rename], &[("g", "one\n2\n3\nfour\nfive\n"), ("x", "c\n")], "Right");
+        let evil = repo.commit(
+            &[left, right],
+            &[("g", "one\ntwo\nthree\nfour\nfive\n"), ("x", "c\n")],
+            "Merge with an extra change",
+        );
+        let last = repo.commit(
+            &[evil],
+            &[("g", "one\ntwo\nthree\nfour\nfive\nsix\n"), ("x", "c\n")],
+            "Append",
+        );
+
+        let commits = vec![c1, c2, c3, side, main, merge, rename, left, right, evil, last];
+        (repo, commits)
+    }
+
+    /// SHAs `get_commits_affecting_file` reports, following every cursor
+    fn history_pages(repo: &TestRepo, path: &str, query: HistoryQuery) -> Vec<Vec<String>> {
+        let mut pages = Vec::new();
+        let mut query = query;
+        loop {
+            let page = get_commits_affecting_file(&repo.repo, path, &query).expect("history");
+            pages.push(page.commits.into_iter().map(|info| info.commit.sha).collect());
+            match page.cursor {
+                Some(cursor) => query.cursor = Some(cursor),
+                None => return pages,
+            }
+        }
+    }
+
+    #[test]
+    fn follow_matches_git_log_follow() {
+        let (repo, _) = branchy_history();
+        let output = std::process::Command::new("git")
+            .arg("-C")
+            .arg(repo.repo.workdir().expect("workdir"))
+            .args(["log", "--follow", "--format=%H", "--", "g"])
+            .output()
+            .expect("run git log");
+        assert!(output.status.success(), "git log failed: {:?}", output);
+        let expected: Vec<String> =
+            String::from_utf8(output.stdout).expect("utf-8").lines().map(str::to_string).collect();
+
+        // `git log --follow` never diffs merges, so it leaves out the evil merge we list
+        let query = HistoryQuery {
+            follow: true,
+            ..Default::default()
+        };
+        let non_merges: Vec<String> = history_pages(&repo, "g", query)
+            .concat()
+            .into_iter()
+            .filter(|sha| {
+                let id = git2::Oid::from_str(sha).expect("valid sha");
+                repo.repo.find_commit(id).expect("find commit").parent_count() < 2
+            })
+            .collect();
+        assert_eq!(non_merges, expected);
+    }
+
+    #[test]
+    fn history_simplifies_merges() {
+        let (repo, commits) = branchy_history();
+        let [c1, _, c3, side, _, _, rename, left, right, evil, last] = commits[..] else {
+            unreachable!()
+        };
+        let shas = |ids: &[git2::Oid]| ids.iter().map(git2::Oid::to_string).collect::<Vec<_>>();
+
+        // The merge of `side` is TREESAME to it, so only `side` itself is listed
+        let query = HistoryQuery {
+            follow: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            history_pages(&repo, "g", query).concat(),
+            shas(&[last, evil, right, left, rename, side, c3, c1])
+        );
+
+        // Without follow the history ends where `g` was created
+        assert_eq!(
+            history_pages(&repo, "g", HistoryQuery::default()).concat(),
+            shas(&[last, evil, right, left, rename])
+        );
+    }
+
+    #[test]
+    fn cursor_pages_neither_repeat_nor_skip() {
+        let (repo, _) = branchy_history();
+        let everything = history_pages(
+            &repo,
+            "g",
+            HistoryQuery {
+                follow: true,
+                full_history: true,
+                ..Default::default()
+            },
+        )
+        .concat();
+
+        for limit in 1..=everything.len() {
+            let pages = history_pages(
+                &repo,
+                "g",
+                HistoryQuery {
+                    follow: true,
+                    full_history: true,
+                    limit: Some(limit),
+                    ..Default::default()
+                },
+            );
+            assert!(pages.iter().all(|page| page.len() <= limit));
+            assert_eq!(pages.concat(), everything, "pages of {}", limit);
+        }
+    }
+
+    #[test]
+    fn history_cursor_round_trips() {
+        let (repo, commits) = branchy_history();
+        let find = |id: &git2::Oid| repo.repo.find_commit(*id).expect("find commit");
+        let mut walk = HistoryWalk::new(&find(&commits[8]), PathBuf::from("g"));
+        walk.push(&find(&commits[7]), PathBuf::from("g"));
+        let cursor = walk.cursor().expect("cursor for a non-empty queue");
+
+        let resumed = HistoryWalk::resume(&repo.repo, &cursor).expect("resume");
+        assert_eq!(resumed.start, commits[8]);
+        assert_eq!(resumed.cursor(), Some(cursor));
+        assert!(HistoryWalk::resume(&repo.repo, "not a cursor").is_err());
+
+        while walk.pop().is_some() {}
+        assert!(walk.cursor().is_none());
+    }
+
+    #[test]
+    fn history_rejects_empty_pages() {
+        let (repo, _) = branchy_history();
+        let query = HistoryQuery {
+            limit: Some(0),
+            ..Default::default()
+        };
+        let result = get_commits_affecting_file(&repo.repo, "g", &query);
+        assert!(matches!(result, Err(GitError::InvalidArgument(_))));
+    }
+
+    /// SHAs `search_commits` returns for a range, page by page, with each page's cursor
+    fn search_pages(
+        repo: &TestRepo,
+        range: &str,
+        limit: usize,
+    ) -> Vec<(Vec<String>, Option<String>)> {
+        let mut pages = Vec::new();
+        let mut cursor = None;
+        loop {
+            let query = CommitSearchQuery {
+                range: Some(range.to_string()),
+                limit: Some(limit),
+                cursor,
+                ..Default::default()
+            };
+            let result = search_commits(&repo.repo, &query).expect("search");
+            let shas = result.commits.into_iter().map(|commit| commit.sha).collect();
+            cursor = result.cursor.clone();
+            pages.push((shas, result.cursor));
+            if cursor.is_none() {
+                return pages;
+            }
+        }
+    }
+
+    #[test]
+    fn search_pages_cover_the_range_once() {
+        let (repo, commits) = branchy_history();
+        let query = CommitSearchQuery {
+            limit: Some(0),
+            ..Default::default()
+        };
+        assert!(matches!(search_commits(&repo.repo, &query), Err(GitError::InvalidArgument(_))));
+
+        let main = commits[4].to_string();
+        for range in ["HEAD", &format!("{}..HEAD", main), &format!("{}...HEAD", commits[3])] {
+            let everything: Vec<String> = search_pages(&repo, range, 100).remove(0).0;
+            for limit in [1, 2, 3] {
+                let pages = search_pages(&repo, range, limit);
+                assert!(pages.iter().all(|(page, _)| page.len() <= limit));
+                let walked: Vec<String> = pages.into_iter().flat_map(|(page, _)| page).collect();
+                assert_eq!(walked, everything, "{} by {}", range, limit);
+            }
+        }
+
+        let mut all: Vec<String> = search_pages(&repo, "HEAD", 100).remove(0).0;
+        all.sort();
+        let mut expected: Vec<String> = commits.iter().map(git2::Oid::to_string).collect();
+        expected.sort();
+        assert_eq!(all, expected);
+    }
+
+    #[test]
+    fn search_cursors_hold_only_the_frontier() {
+        let repo = TestRepo::new();
+        let mut head = repo.commit(&[], &[("f", "0\n")], "0");
+        for i in 1..40 {
+            head = repo.commit(&[head], &[("f", &format!("{}\n", i))], &i.to_string());
+        }
+
+        for (_, cursor) in search_pages(&repo, "HEAD", 5) {
+            let Some(cursor) = cursor else {
+                continue;
+            };
+            let bytes = URL_SAFE_NO_PAD.decode(cursor).expect("decode cursor");
+            let position: RangePosition = serde_json::from_slice(&bytes).expect("parse cursor");
+            assert_eq!(position.frontier.len(), 1);
+            assert!(position.visited.is_empty());
+        }
+    }
+
+    #[test]
+    fn search_cursors_go_stale_when_the_range_moves() {
+        let (repo, commits) = branchy_history();
+        let first = search_pages(&repo, "HEAD", 2).remove(0);
+        repo.commit(&[commits[10]], &[("g", "new\n")], "Newer");
+
+        let query = CommitSearchQuery {
+            limit: Some(2),
+            cursor: first.1,
+            ..Default::default()
+        };
+        match search_commits(&repo.repo, &query) {
+            Err(GitError::InvalidArgument(message)) => assert!(message.contains("stale"), "{}", message),
+            other => panic!("expected a stale cursor, got {:?}", other.ok().map(|r| r.commits.len())),
+        }
+
+        let query = CommitSearchQuery {
+            cursor: Some("not a cursor".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(search_commits(&repo.repo, &query), Err(GitError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn pickaxe_ignores_pure_renames() {
+        let repo = TestRepo::new();
+        let add = repo.commit(&[], &[("a", "let needle = 1;\nother\nlines\nhere\n")], "Add");
+        let rename =
+            repo.commit(&[add], &[("b", "let needle = 1;\nother\nlines\nhere\n")], "Rename");
+        let more = repo.commit(
+            &[rename],
+            &[("c", "let needle = 1;\nother\nlines\nhere\nneedle()\n")],
+            "Rename and call",
+        );
+
+        let search = |limit| {
+            pickaxe_search(
+                &repo.repo,
+                &PickaxeQuery {
+                    pattern: "needle".to_string(),
+                    limit,
+                    ..Default::default()
+                },
+            )
+        };
+        assert!(matches!(search(Some(0)), Err(GitError::InvalidArgument(_))));
+
+        let result = search(None).expect("pickaxe");
+        let matched: Vec<(String, String, usize, usize)> = result
+            .commits
+            .into_iter()
+            .flat_map(|info| {
+                let sha = info.commit.sha;
+                info.files
+                    .into_iter()
+                    .map(move |file| (sha.clone(), file.path, file.added, file.removed))
+            })
+            .collect();
+        assert_eq!(
+            matched,
+            vec![
+                (more.to_string(), "c".to_string(), 1, 0),
+                (add.to_string(), "a".to_string(), 1, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn pickaxe_pages_resume_and_go_stale() {
+        let repo = TestRepo::new();
+        let mut head = repo.commit(&[], &[("f", "start\n")], "Start");
+        let mut content = String::from("start\n");
+        let mut expected = Vec::new();
+        for i in 0..5 {
+            content.push_str(&format!("needle {}\n", i));
+            let needle = repo.commit(&[head], &[("f", &content)], &format!("Needle {}", i));
+            head = repo.commit(&[needle], &[("f", &content), ("g", &i.to_string())], "Other");
+            expected.insert(0, needle.to_string());
+        }
+
+        let page = |cursor: Option<String>| {
+            pickaxe_search(
+                &repo.repo,
+                &PickaxeQuery {
+                    pattern: "needle".to_string(),
+                    limit: Some(2),
+                    cursor,
+                    ..Default::default()
+                },
+            )
+        };
+        let mut found = Vec::new();
+        let mut cursor = None;
+        let mut first_cursor = None;
+        loop {
+            let result = page(cursor).expect("pickaxe");
+            found.extend(result.commits.into_iter().map(|info| info.commit.sha));
+            first_cursor = first_cursor.or(result.cursor.clone());
+            cursor = result.cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert_eq!(found, expected);
+
+        repo.commit(&[head], &[("f", &content)], "Newer");
+        match page(first_cursor) {
+            Err(GitError::InvalidArgument(message)) => assert!(message.contains("stale"), "{}", message),
+            other => panic!("expected a stale cursor, got {:?}", other.ok().map(|r| r.commits.len())),
+        }
+    }
+
+    /// SHAs `get_line_history` reports for a range of `f`
+    fn line_history(repo: &TestRepo, start_line: usize, end_line: usize) -> Vec<String> {
+        let query = LineHistoryQuery {
+            start_line: Some(start_line),
+            end_line: Some(end_line),
+            ..Default::default()
+        };
+        get_line_history(&repo.repo, "f", &query)
+            .expect("line history")
+            .entries
+            .into_iter()
+            .map(|entry| entry.commit.sha)
+            .collect()
+    }
+
+    #[test]
+    fn line_history_follows_the_branch_that_added_lines() {
+        let repo = TestRepo::new();
+        let base = repo.commit(&[], &[("f", "alpha\n0\n")], "Base");
+        let side = repo.commit(&[base], &[("f", "alpha\n0\nbeta\n1\n")], "Add beta");
+        let main = repo.commit(&[base], &[("f", "alpha\n2\n")], "Edit alpha");
+        repo.commit(&[main, side], &[("f", "alpha\n2\nbeta\n1\n")], "Merge");
+
+        // Neither parent explains the file, but `side` explains each range
+        assert_eq!(line_history(&repo, 3, 4), vec![side.to_string()]);
+        assert_eq!(line_history(&repo, 1, 2), vec![main.to_string(), base.to_string()]);
+    }
+
+    #[test]
+    fn line_history_follows_every_parent_of_an_evil_merge() {
+        let repo = TestRepo::new();
+        let base = repo.commit(&[], &[("f", "a\nb\n")], "Base");
+        let left = repo.commit(&[base], &[("f", "A\nb\n")], "Left");
+        let right = repo.commit(&[base], &[("f", "a\nB\n")], "Right");
+        let merge = repo.commit(&[left, right], &[("f", "A!\nB\n")], "Merge with an extra change");
+
+        let shas = |ids: &[git2::Oid]| ids.iter().map(git2::Oid::to_string).collect::<Vec<_>>();
+        assert_eq!(line_history(&repo, 1, 2), shas(&[merge, right, left, base]));
+    }
+
+    #[test]
+    fn line_history_arguments_are_validated() {
+        let repo = TestRepo::new();
+        let id = repo.commit(&[], &[("f", "fn main() {\n}\n")], "A");
+
+        let error = |query: LineHistoryQuery| match get_line_history(&repo.repo, "f", &query) {
+            Err(GitError::InvalidArgument(message)) => message,
+            other => panic!("expected invalid arguments, got {:?}", other.ok().map(|h| h.entries.len())),
+        };
+        let function = || Some("main".to_string());
+
+        assert!(error(LineHistoryQuery::default()).contains("`start_line`"));
+        assert!(error(LineHistoryQuery {
+            function: function(),
+            start_line: Some(1),
+            ..Default::default()
+        })
+        .contains("not both"));
+        assert!(error(LineHistoryQuery {
+            function: function(),
+            end_line: Some(2),
+            ..Default::default()
+        })
+        .contains("`end_line`"));
+        assert_eq!(
+            error(LineHistoryQuery {
+                start_line: Some(1),
+                limit: Some(0),
+                ..Default::default()
+            }),
+            "limit must be at least 1"
+        );
+
+        let query = LineHistoryQuery {
+            function: function(),
+            ..Default::default()
+        };
+        let history = get_line_history(&repo.repo, "f", &query).expect("line history");
+        assert_eq!(history.entries.len(), 1);
+        assert_eq!(history.start_sha, id.to_string());
+    }
+
+    #[test]
+    fn symbol_history_rejects_what_it_cannot_trace() {
+        let repo = TestRepo::new();
+        let id = repo.commit(&[], &[("lib.rs", "fn main() {}\n"), ("notes.txt", "main\n")], "A");
+
+        let error = |file: &str, symbol: &str, limit| {
+            let query = SymbolHistoryQuery {
+                limit,
+                ..Default::default()
+            };
+            match get_symbol_history(&repo.repo, file, symbol, &query) {
+                Err(GitError::InvalidArgument(message)) => message,
+                other => panic!("expected invalid arguments, got {:?}", other.ok().map(|h| h.kind)),
+            }
+        };
+        assert_eq!(
+            error("lib.rs", "missing", None),
+            format!("no symbol `missing` in lib.rs at {}", id)
+        );
+        assert!(error("notes.txt", "main", None).starts_with("no symbol parser for notes.txt"));
+        assert_eq!(error("lib.rs", "main", Some(0)), "limit must be at least 1");
+
+        let query = SymbolHistoryQuery {
+            limit: Some(1),
+            ..Default::default()
+        };
+        let history = get_symbol_history(&repo.repo, "lib.rs", "main", &query).expect("history");
+        assert_eq!(history.entries.len(), 1);
+        assert_eq!(history.start_sha, id.to_string());
+    }
+
+    #[test]
+    fn as_of_moves_the_head_of_a_range() {
+        let repo = TestRepo::new();
+        let first = repo.commit(&[], &[("f", "1\n")], "First");
+        let second = repo.commit(&[first], &[("f", "2\n")], "Second");
+        repo.commit(&[second], &[("f", "3\n")], "Third");
+
+        // Half an hour after `second` was committed
+        let committed = repo.repo.find_commit(second).expect("find commit").time().seconds();
+        let as_of = DateTime::from_timestamp(committed + 1_800, 0)
+            .expect("valid timestamp")
+            .to_rfc3339();
+        let moved = |range: Option<&str>| {
+            range_as_of(&repo.repo, range, Some(&as_of)).expect("range as of")
+        };
+
+        assert_eq!(moved(None), Some(second.to_string()));
+        assert_eq!(moved(Some("HEAD")), Some(second.to_string()));
+        assert_eq!(moved(Some(&format!("{}..", first))), Some(format!("{}..{}", first, second)));
+        assert_eq!(moved(Some("main...")), Some(format!("main...{}", second)));
+        assert_eq!(
+            range_as_of(&repo.repo, Some("HEAD~1..HEAD"), None).expect("range"),
+            Some("HEAD~1..HEAD".to_string())
+        );
+    }
+
+    #[test]
+    fn as_of_moves_both_ends_of_a_diff_range() {
+        let repo = TestRepo::new();
+        let first = repo.commit(&[], &[("f", "1\n")], "First");
+        let second = repo.commit(&[first], &[("f", "2\n")], "Second");
+        repo.commit(&[second], &[("f", "3\n")], "Third");
+
+        // Half an hour after each commit
+        let after = |id: git2::Oid| {
+            let committed = repo.repo.find_commit(id).expect("find commit").time().seconds();
+            DateTime::from_timestamp(committed + 1_800, 0)
+                .expect("valid timestamp")
+                .to_rfc3339()
+        };
+        let (base_as_of, head_as_of) = (after(first), after(second));
+
+        for range in ["HEAD..HEAD", "..", "HEAD...HEAD"] {
+            let summary =
+                summarize_range(&repo.repo, range, Some(&base_as_of), Some(&head_as_of))
+                    .expect("summarize");
+            assert_eq!(summary.base_commit.sha, first.to_string(), "{}", range);
+            assert_eq!(summary.head_commit.sha, second.to_string(), "{}", range);
+        }
+
+        let head_only = summarize_range(&repo.repo, "HEAD~2..", None, Some(&head_as_of))
+            .expect("summarize");
+        assert_eq!(head_only.base_commit.sha, first.to_string());
+        assert_eq!(head_only.head_commit.sha, second.to_string());
+    }
+}
@@ -1 +1,4 @@
+pub mod commit_cache;
 pub mod git_helpers;
+pub mod repo_registry;
+pub mod symbols;
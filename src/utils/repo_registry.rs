@@ -0,0 +1,249 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use git2::Repository;
+use tracing::{debug, info, warn};
+
+use crate::utils::git_helpers::GitError;
+
+/// Process-wide registry, configured once at startup
+static REGISTRY: OnceLock<RepoRegistry> = OnceLock::new();
+
+/// How many directory levels below a root are scanned for repositories
+const MAX_DISCOVERY_DEPTH: usize = 5;
+
+/// Directories never descended into while scanning roots
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target"];
+
+/// Named repositories this server can serve
+///
+/// Requests pick a repository by name; requests without one use the default.
+#[derive(Debug, Default)]
+pub struct RepoRegistry {
+    repos: BTreeMap<String, PathBuf>,
+    paths: HashSet<PathBuf>,
+    default: Option<String>,
+}
+
+impl RepoRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the repository containing `path`, making it the default if it is the first one
+    ///
+    /// The repository root is located with `Repository::discover`, so any path inside a
+    /// working tree works. Without an explicit name the repository is named after its
+    /// directory, with a numeric suffix if that name is taken; an explicit name that is
+    /// already taken by another repository is an error. Registering the same repository
+    /// twice returns the existing name.
+    pub fn insert(&mut self, name: Option<String>, path: &Path) -> Result<String, GitError> {
+        let repo = Repository::discover(path)?;
+        let root = repo_root(&repo);
+
+        if self.paths.contains(&root) {
+            if let Some((existing, _)) = self.repos.iter().find(|(_, p)| **p == root) {
+                return Ok(existing.clone());
+            }
+        }
+
+        let name = match name {
+            Some(name) => {
+                if let Some(existing) = self.repos.get(&name) {
+                    return Err(GitError::InvalidArgument(format!(
+                        "repository name '{}' is already used for {:?}",
+                        name, existing
+                    )));
+                }
+                name
+            }
+            None => self.unique_name(&name_for_path(&root)),
+        };
+        info!("Registered repository '{}' at {:?}", name, root);
+
+        if self.default.is_none() {
+            self.default = Some(name.clone());
+        }
+        self.paths.insert(root.clone());
+        self.repos.insert(name.clone(), root);
+
+        // Linked worktrees are served alongside the repository they belong to
+        if let Ok(worktrees) = repo.worktrees() {
+            for worktree in worktrees.iter().flatten() {
+                match repo.find_worktree(worktree) {
+                    Ok(worktree) if worktree.validate().is_ok() => {
+                        let worktree_path = worktree.path().to_path_buf();
+                        if let Err(e) = self.insert(None, &worktree_path) {
+                            warn!("Skipping worktree {:?}: {}", worktree_path, e);
+                        }
+                    }
+                    Ok(_) => debug!("Skipping stale worktree '{}'", worktree),
+                    Err(e) => warn!("Failed to load worktree '{}': {}", worktree, e),
+                }
+            }
+        }
+
+        Ok(name)
+    }
+
+    /// Register every repository found below a root directory
+    ///
+    /// Finds working trees (including nested ones and submodules) and bare repositories,
+    /// up to `MAX_DISCOVERY_DEPTH` levels deep. Returns the number of repositories added.
+    pub fn discover(&mut self, root: &Path) -> usize {
+        let before = self.repos.len();
+        self.scan(root, 0);
+        let found = self.repos.len() - before;
+        info!("Discovered {} repositories under {:?}", found, root);
+        found
+    }
+
+    fn scan(&mut self, dir: &Path, depth: usize) {
+        if is_repository(dir) {
+            if let Err(e) = self.insert(None, dir) {
+                warn!("Skipping repository at {:?}: {}", dir, e);
+            }
+            // The inside of a bare repository is git's own bookkeeping
+            if !dir.join(".git").exists() {
+                return;
+            }
+        }
+
+        if depth >= MAX_DISCOVERY_DEPTH {
+            return;
+        }
+
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                debug!("Cannot read {:?}: {}", dir, e);
+                return;
+            }
+        };
+
+        let mut children: Vec<PathBuf> = entries
+            .flatten()
+            .filter(|entry| entry.file_type().map(|t| t.is_dir()).unwrap_or(false))
+            .map(|entry| entry.path())
+            .filter(|path| {
+                let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                !name.starts_with('.') && !SKIPPED_DIRS.contains(&name)
+            })
+            .collect();
+        children.sort();
+
+        for child in children {
+            self.scan(&child, depth + 1);
+        }
+    }
+
+    /// Resolve a repository name (or the default) to its path
+    pub fn resolve(&self, name: Option<&str>) -> Result<(&str, &Path), GitError> {
+        let name = match name {
+            Some(name) => name,
+            None => self
+                .default
+                .as_deref()
+                .ok_or_else(|| GitError::UnknownRepository("no default repository".to_string()))?,
+        };
+
+        self.repos
+            .get_key_value(name)
+            .map(|(name, path)| (name.as_str(), path.as_path()))
+            .ok_or_else(|| GitError::UnknownRepository(name.to_string()))
+    }
+
+    /// Name of the repository used when a request does not pick one
+    pub fn default_name(&self) -> Option<&str> {
+        self.default.as_deref()
+    }
+
+    /// Iterate over registered repositories as `(name, path)` pairs
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Path)> {
+        self.repos
+            .iter()
+            .map(|(name, path)| (name.as_str(), path.as_path()))
+    }
+
+    /// Append a numeric suffix until the name is free
+    fn unique_name(&self, base: &str) -> String {
+        let mut name = base.to_string();
+        let mut suffix = 2;
+        while self.repos.contains_key(&name) {
+            name = format!("{}-{}", base, suffix);
+            suffix += 1;
+        }
+        name
+    }
+}
+
+/// Install the registry used for the lifetime of the process
+///
+/// Must be called before the first request; later calls are ignored.
+pub fn init(registry: RepoRegistry) {
+    if REGISTRY.set(registry).is_err() {
+        debug!("Repository registry already initialized");
+    }
+}
+
+/// Get the process-wide registry
+///
+/// Falls back to an empty registry when `init` was never called.
+pub fn registry() -> &'static RepoRegistry {
+    REGISTRY.get_or_init(RepoRegistry::new)
+}
+
+/// Open a registered repository by name, or the default one
+pub fn open(name: Option<&str>) -> Result<Repository, GitError> {
+    let (name, path) = registry().resolve(name)?;
+    debug!("Opening repository '{}' at path: {:?}", name, path);
+    Ok(Repository::open(path)?)
+}
+
+/// Whether a directory is a working tree (`.git` dir or file) or a bare repository
+fn is_repository(dir: &Path) -> bool {
+    dir.join(".git").exists()
+        || (dir.join("HEAD").is_file() && dir.join("objects").is_dir() && dir.join("refs").is_dir())
+}
+
+/// The directory a repository is opened from: its working tree, or the git dir if bare
+fn repo_root(repo: &Repository) -> PathBuf {
+    let root = repo.workdir().unwrap_or_else(|| repo.path());
+    root.canonicalize().unwrap_or_else(|_| root.to_path_buf())
+}
+
+/// Derive a display name from a repository's directory
+fn name_for_path(path: &Path) -> String {
+    path.file_name()
+        .map(|name| {
+            let name = name.to_string_lossy();
+            name.strip_suffix(".git").unwrap_or(&name).to_string()
+        })
+        .unwrap_or_else(|| "repo".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explicit_names_must_be_unique() {
+        let first = tempfile::tempdir().expect("create temp dir");
+        let second = tempfile::tempdir().expect("create temp dir");
+        Repository::init(first.path()).expect("init repository");
+        Repository::init(second.path()).expect("init repository");
+
+        let mut registry = RepoRegistry::new();
+        let name = Some("app".to_string());
+        assert_eq!(registry.insert(name.clone(), first.path()).expect("insert"), "app");
+        // The same repository again keeps its name
+        assert_eq!(registry.insert(name.clone(), first.path()).expect("insert"), "app");
+        assert!(matches!(
+            registry.insert(name, second.path()),
+            Err(GitError::InvalidArgument(_))
+        ));
+        assert_eq!(registry.iter().count(), 1);
+    }
+}
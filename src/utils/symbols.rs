@@ -0,0 +1,360 @@
+use std::path::Path;
+
+use tree_sitter::{Node, Parser};
+
+use crate::utils::git_helpers::GitError;
+
+/// Languages whose definitions can be located by name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    TypeScript,
+    Tsx,
+    Python,
+    Go,
+}
+
+/// A named definition located in a source file
+pub struct Symbol {
+    /// Tree-sitter node kind of the definition, e.g. `function_item`
+    pub kind: String,
+    /// First line of the definition (1-based)
+    pub start_line: usize,
+    /// Last line of the definition, inclusive
+    pub end_line: usize,
+    /// Full lines spanned by the definition
+    pub text: String,
+}
+
+/// Node kinds that define something with a `name` field
+struct LanguageSpec {
+    definitions: &'static [&'static str],
+    /// Node kinds whose definitions qualify a nested name, with the field naming them
+    containers: &'static [(&'static str, &'static str)],
+}
+
+const RUST: LanguageSpec = LanguageSpec {
+    definitions: &[
+        "function_item",
+        "function_signature_item",
+        "struct_item",
+        "enum_item",
+        "union_item",
+        "trait_item",
+        "mod_item",
+        "macro_definition",
+        "const_item",
+        "static_item",
+        "type_item",
+    ],
+    containers: &[("impl_item", "type"), ("trait_item", "name"), ("mod_item", "name")],
+};
+
+const TYPESCRIPT: LanguageSpec = LanguageSpec {
+    definitions: &[
+        "function_declaration",
+        "generator_function_declaration",
+        "class_declaration",
+        "abstract_class_declaration",
+        "interface_declaration",
+        "method_definition",
+        "abstract_method_signature",
+        "enum_declaration",
+        "type_alias_declaration",
+        "variable_declarator",
+    ],
+    containers: &[
+        ("class_declaration", "name"),
+        ("abstract_class_declaration", "name"),
+        ("interface_declaration", "name"),
+        ("internal_module", "name"),
+    ],
+};
+
+const PYTHON: LanguageSpec = LanguageSpec {
+    definitions: &["function_definition", "class_definition"],
+    containers: &[("class_definition", "name")],
+};
+
+const GO: LanguageSpec = LanguageSpec {
+    definitions: &["function_declaration", "method_declaration", "type_spec"],
+    containers: &[],
+};
+
+/// Values that make a `const name = ...` declarator count as a function
+const FUNCTION_VALUES: &[&str] = &[
+    "arrow_function",
+    "function_expression",
+    "function",
+    "generator_function",
+];
+
+impl Language {
+    /// Pick the language for a file from its extension
+    pub fn from_path(path: &str) -> Option<Self> {
+        let extension = Path::new(path).extension()?.to_str()?;
+        match extension {
+            "rs" => Some(Language::Rust),
+            "ts" | "mts" | "cts" => Some(Language::TypeScript),
+            "tsx" | "js" | "jsx" | "mjs" | "cjs" => Some(Language::Tsx),
+            "py" | "pyi" => Some(Language::Python),
+            "go" => Some(Language::Go),
+            _ => None,
+        }
+    }
+
+    fn grammar(self) -> tree_sitter::Language {
+        match self {
+            Language::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Language::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Language::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+            Language::Python => tree_sitter_python::LANGUAGE.into(),
+            Language::Go => tree_sitter_go::LANGUAGE.into(),
+        }
+    }
+
+    fn spec(self) -> &'static LanguageSpec {
+        match self {
+            Language::Rust => &RUST,
+            Language::TypeScript | Language::Tsx => &TYPESCRIPT,
+            Language::Python => &PYTHON,
+            Language::Go => &GO,
+        }
+    }
+}
+
+/// Locate the first definition called `name` in a source file
+///
+/// `name` may be qualified by its enclosing impl, trait, class or Go receiver type, as
+/// `Type::name` or `Type.name`, to pick one of several definitions sharing a name.
+pub fn find_symbol(language: Language, content: &str, name: &str) -> Result<Option<Symbol>, GitError> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&language.grammar())
+        .map_err(|e| GitError::Other(format!("Failed to load {:?} grammar: {}", language, e)))?;
+    let tree = parser
+        .parse(content, None)
+        .ok_or_else(|| GitError::Other(format!("Failed to parse {:?} source", language)))?;
+
+    let (qualifier, name) = match name.rsplit_once("::").or_else(|| name.rsplit_once('.')) {
+        Some((qualifier, name)) => (Some(last_segment(qualifier)), name),
+        None => (None, name),
+    };
+
+    let source = content.as_bytes();
+    let Some(node) = find_node(tree.root_node(), language.spec(), source, qualifier, name, None) else {
+        return Ok(None);
+    };
+
+    // Decorators belong to the Python definition they wrap
+    let span = match node.parent() {
+        Some(parent) if parent.kind() == "decorated_definition" => parent,
+        _ => node,
+    };
+    let start_line = span.start_position().row + 1;
+    let end_line = span.end_position().row + 1;
+    let text = content
+        .split_inclusive('\n')
+        .skip(start_line - 1)
+        .take(end_line + 1 - start_line)
+        .collect();
+
+    Ok(Some(Symbol {
+        kind: node.kind().to_string(),
+        start_line,
+        end_line,
+        text,
+    }))
+}
+
+/// Depth-first search for a definition, tracking the name of the innermost container
+fn find_node<'t>(
+    node: Node<'t>,
+    spec: &LanguageSpec,
+    source: &[u8],
+    qualifier: Option<&str>,
+    name: &str,
+    container: Option<&str>,
+) -> Option<Node<'t>> {
+    if spec.definitions.contains(&node.kind()) && definition_name(node, source) == Some(name) {
+        let owner = receiver_type(node, source).or(container);
+        if qualifier.is_none() || qualifier == owner {
+            return Some(node);
+        }
+    }
+
+    let own_name = spec
+        .containers
+        .iter()
+        .find(|(kind, _)| *kind == node.kind())
+        .and_then(|(_, field)| node.child_by_field_name(field))
+        .and_then(|name_node| name_node.utf8_text(source).ok())
+        .map(type_name);
+    let container = own_name.or(container);
+
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node.named_children(&mut cursor).collect();
+    children
+        .into_iter()
+        .find_map(|child| find_node(child, spec, source, qualifier, name, container))
+}
+
+/// The name a definition node declares
+fn definition_name<'s>(node: Node, source: &'s [u8]) -> Option<&'s str> {
+    // Only `const name = () => ...` style declarators define a function
+    if node.kind() == "variable_declarator" {
+        let value = node.child_by_field_name("value")?;
+        if !FUNCTION_VALUES.contains(&value.kind()) {
+            return None;
+        }
+    }
+    node.child_by_field_name("name")?.utf8_text(source).ok()
+}
+
+/// The receiver type of a Go method, e.g. `Server` for `func (s *Server) Start()`
+fn receiver_type<'s>(node: Node, source: &'s [u8]) -> Option<&'s str> {
+    if node.kind() != "method_declaration" {
+        return None;
+    }
+    let receiver = node.child_by_field_name("receiver")?;
+    let mut stack = vec![receiver];
+    while let Some(current) = stack.pop() {
+        if current.kind() == "type_identifier" {
+            return current.utf8_text(source).ok();
+        }
+        let mut cursor = current.walk();
+        let children: Vec<Node> = current.named_children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev());
+    }
+    None
+}
+
+/// Reduce a type as written in an impl header to its bare name: `a::Foo<T>` becomes `Foo`
+fn type_name(text: &str) -> &str {
+    let without_generics = text.split('<').next().unwrap_or(text);
+    last_segment(without_generics.trim())
+}
+
+/// The last segment of a `::` or `.` separated path
+fn last_segment(path: &str) -> &str {
+    path.rsplit("::")
+        .next()
+        .and_then(|segment| segment.rsplit('.').next())
+        .unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Kind and line span of the definition `name` resolves to
+    fn locate(language: Language, content: &str, name: &str) -> Option<(String, usize, usize)> {
+        find_symbol(language, content, name)
+            .expect("parse")
+            .map(|symbol| (symbol.kind, symbol.start_line, symbol.end_line))
+    }
+
+    #[test]
+    fn qualified_names_pick_the_enclosing_type() {
+        let rust = "\
+struct Config;
+struct Args;
+
+impl Args {
+    fn parse() {}
+}
+
+impl<T> crate::config::Config<T> {
+    fn parse() {
+        todo!()
+    }
+}
+";
+        let function = |start, end| Some(("function_item".to_string(), start, end));
+        assert_eq!(locate(Language::Rust, rust, "parse"), function(5, 5));
+        assert_eq!(locate(Language::Rust, rust, "Config::parse"), function(9, 11));
+        assert_eq!(locate(Language::Rust, rust, "config::Config::parse"), function(9, 11));
+        assert_eq!(locate(Language::Rust, rust, "Other::parse"), None);
+
+        let typescript = "\
+class Client {
+  start() {}
+}
+class Server {
+  start() {
+    listen();
+  }
+}
+";
+        let method = Some(("method_definition".to_string(), 5, 7));
+        assert_eq!(locate(Language::TypeScript, typescript, "Server.start"), method);
+    }
+
+    #[test]
+    fn go_methods_are_qualified_by_their_receiver() {
+        let go = "\
+package main
+
+func (c Client) Start() {}
+
+func (s *Server) Start() {
+	s.listen()
+}
+
+func Start() {}
+";
+        let method = |start, end| Some(("method_declaration".to_string(), start, end));
+        assert_eq!(locate(Language::Go, go, "Client.Start"), method(3, 3));
+        assert_eq!(locate(Language::Go, go, "Server.Start"), method(5, 7));
+        assert_eq!(locate(Language::Go, go, "Start"), method(3, 3));
+    }
+
+    #[test]
+    fn python_decorators_belong_to_their_definition() {
+        let python = "\
+import functools
+
+@functools.cache
+@trace
+def load(path):
+    return open(path).read()
+
+class Store:
+    @property
+    def size(self):
+        return 0
+";
+        let symbol = find_symbol(Language::Python, python, "load")
+            .expect("parse")
+            .expect("a symbol");
+        assert_eq!(symbol.kind, "function_definition");
+        assert_eq!((symbol.start_line, symbol.end_line), (3, 6));
+        assert!(symbol.text.starts_with("@functools.cache\n@trace\ndef load"));
+
+        let method = Some(("function_definition".to_string(), 9, 11));
+        assert_eq!(locate(Language::Python, python, "Store.size"), method);
+    }
+
+    #[test]
+    fn function_valued_consts_are_definitions() {
+        let typescript = "\
+const limit = 10;
+export const handler = async (event: Event) => {
+  return limit;
+};
+const legacy = function () {};
+";
+        let declarator = |start, end| Some(("variable_declarator".to_string(), start, end));
+        assert_eq!(locate(Language::TypeScript, typescript, "handler"), declarator(2, 4));
+        assert_eq!(locate(Language::Tsx, typescript, "legacy"), declarator(5, 5));
+        assert_eq!(locate(Language::TypeScript, typescript, "limit"), None);
+    }
+
+    #[test]
+    fn unknown_symbols_are_not_found() {
+        assert_eq!(locate(Language::Rust, "fn main() {}\n", "missing"), None);
+        assert_eq!(locate(Language::Rust, "", "main"), None);
+        assert_eq!(Language::from_path("notes.txt"), None);
+        assert_eq!(Language::from_path("src/app.jsx"), Some(Language::Tsx));
+    }
+}